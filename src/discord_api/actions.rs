@@ -0,0 +1,62 @@
+use serde::de::IgnoredAny;
+use serde_json::json;
+
+use crate::discord_api::messages::MessageGetter;
+use crate::discord_api::transport::Transport;
+use crate::models::discord::MessageBody;
+use crate::prelude::*;
+
+/// Trait for acting on messages in other channels than the announcement channel.
+pub trait MessageActions {
+    /// Reacts to the message `message_id` with `emoji` as the bot, a unicode emoji or
+    /// `name:id` for custom ones.
+    fn add_reaction(&self, channel_id: &str, message_id: &str, emoji: &str) -> Result<()>;
+
+    /// Posts `message_body` to `channel_id`, e.g. a reply to a message there.
+    fn send_message_to(&self, channel_id: &str, message_body: MessageBody) -> Result<()>;
+
+    /// Publishes the message `message_id` of the announcement channel `channel_id` to the
+    /// servers following it.
+    fn crosspost_message(&self, channel_id: &str, message_id: &str) -> Result<()>;
+}
+
+impl<T: Transport> MessageActions for MessageGetter<T> {
+    fn add_reaction(&self, channel_id: &str, message_id: &str, emoji: &str) -> Result<()> {
+        self.put(
+            format!(
+                "/channels/{channel_id}/messages/{message_id}/reactions/{}/@me",
+                encode_emoji(emoji)
+            )
+            .as_str(),
+        )
+    }
+
+    fn send_message_to(&self, channel_id: &str, message_body: MessageBody) -> Result<()> {
+        let _: IgnoredAny = self.post_json(
+            format!("/channels/{channel_id}/messages").as_str(),
+            &message_body,
+        )?;
+        Ok(())
+    }
+
+    fn crosspost_message(&self, channel_id: &str, message_id: &str) -> Result<()> {
+        let _: IgnoredAny = self.post_json(
+            format!("/channels/{channel_id}/messages/{message_id}/crosspost").as_str(),
+            &json!({}),
+        )?;
+        Ok(())
+    }
+}
+
+/// Percent-encodes `emoji` for use in a path, leaving the `:` of custom emojis as it is.
+fn encode_emoji(emoji: &str) -> String {
+    emoji
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
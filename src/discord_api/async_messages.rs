@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+use futures::future;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use log::debug;
+
+use crate::discord_api::async_transport::{AsyncReqwestTransport, AsyncRetrying, AsyncTransport};
+use crate::discord_api::history::{cursor_at, history_stream, Order};
+use crate::discord_api::messages::messages_path;
+use crate::discord_api::request::{discord_headers, read_response};
+use crate::discord_api::retry::RetryPolicy;
+use crate::discord_api::transport::{Body, HttpRequest, Method};
+use crate::models::discord::{Message, MessageBody, SentMessage, User};
+use crate::prelude::*;
+use crate::utils::config::Config;
+use crate::utils::period::Period;
+
+/// The async counterpart of `GetMsgs`.
+pub trait AsyncGetMsgs: Sync {
+    /// See `GetMsgs::get_messages`.
+    fn get_messages(
+        &self,
+        channel_id: &str,
+        after: Option<String>,
+        limit: Option<i32>,
+    ) -> impl Future<Output = Result<Vec<Message>>> + Send;
+
+    /// See `GetMsgs::get_messages_before`.
+    fn get_messages_before(
+        &self,
+        channel_id: &str,
+        before: Option<String>,
+        limit: Option<i32>,
+    ) -> impl Future<Output = Result<Vec<Message>>> + Send;
+
+    /// Retrieves every user that reacted to `msg` with `emoji`.
+    ///
+    /// `emoji` is either a unicode emoji or `name:id` for custom emojis.
+    fn get_reaction_users(
+        &self,
+        msg: &Message,
+        emoji: &str,
+    ) -> impl Future<Output = Result<Vec<User>>> + Send;
+}
+
+/// The async counterpart of `SendMsgs`.
+pub trait AsyncSendMsgs: Sync {
+    fn send_messages(
+        &self,
+        message_body: MessageBody,
+    ) -> impl Future<Output = Result<SentMessage>> + Send;
+}
+
+pub struct AsyncMessageGetter<T: AsyncTransport = AsyncRetrying<AsyncReqwestTransport>> {
+    pub(crate) transport: T,
+    base_url: String,
+    token: String,
+    out_channel_id: String,
+}
+
+impl AsyncMessageGetter {
+    pub fn new() -> Self {
+        Self::from_config(Config::get())
+    }
+
+    /// Creates an `AsyncMessageGetter` talking to `config.base_url` with the configured
+    /// `RetryPolicy`.
+    pub fn from_config(config: &Config) -> Self {
+        let transport = AsyncRetrying::new(
+            AsyncReqwestTransport::new(),
+            RetryPolicy::from_config(config),
+        );
+        Self::with_transport(config, transport)
+    }
+}
+
+impl<T: AsyncTransport> AsyncMessageGetter<T> {
+    pub fn with_transport(config: &Config, transport: T) -> Self {
+        Self {
+            transport,
+            base_url: config.base_url.clone(),
+            token: config.token.clone(),
+            out_channel_id: config.out_channel_id.clone(),
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<String> {
+        let url = format!("{}{}", self.base_url, path);
+        debug!("{:#?}", url);
+        let res = self
+            .transport
+            .send(HttpRequest {
+                method: Method::Get,
+                url,
+                headers: discord_headers(self.token.as_str()),
+                body: None,
+                max_body_bytes: None,
+            })
+            .await?;
+        read_response(res)
+    }
+}
+
+impl<T: AsyncTransport> AsyncGetMsgs for AsyncMessageGetter<T> {
+    async fn get_messages(
+        &self,
+        channel_id: &str,
+        after: Option<String>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Message>> {
+        let limit = limit.unwrap_or(1);
+        let path = messages_path(channel_id, after.as_deref(), None, Some(limit));
+        let body = self.get(path.as_str()).await?;
+        Ok(serde_json::from_str(body.as_str())?)
+    }
+
+    async fn get_messages_before(
+        &self,
+        channel_id: &str,
+        before: Option<String>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Message>> {
+        let limit = limit.unwrap_or(1);
+        let path = messages_path(channel_id, None, before.as_deref(), Some(limit));
+        let body = self.get(path.as_str()).await?;
+        Ok(serde_json::from_str(body.as_str())?)
+    }
+
+    async fn get_reaction_users(&self, msg: &Message, emoji: &str) -> Result<Vec<User>> {
+        let mut users: Vec<User> = Vec::new();
+        loop {
+            let mut path = format!(
+                "/channels/{}/messages/{}/reactions/{}?limit=100",
+                msg.channel_id, msg.id, emoji
+            );
+            if let Some(last) = users.last() {
+                path.push_str(format!("&after={}", last.id).as_str());
+            }
+
+            let page: Vec<User> = serde_json::from_str(self.get(path.as_str()).await?.as_str())?;
+            let is_last_page = page.len() < 100;
+            users.extend(page);
+            if is_last_page {
+                return Ok(users);
+            }
+        }
+    }
+}
+
+impl<T: AsyncTransport> AsyncSendMsgs for AsyncMessageGetter<T> {
+    async fn send_messages(&self, message_body: MessageBody) -> Result<SentMessage> {
+        let url = format!(
+            "{}/channels/{}/messages",
+            self.base_url, self.out_channel_id
+        );
+        let res = self
+            .transport
+            .send(HttpRequest {
+                method: Method::Post,
+                url,
+                headers: discord_headers(self.token.as_str()),
+                body: Some(Body::Json(serde_json::to_string(&message_body)?)),
+                max_body_bytes: None,
+            })
+            .await?;
+        Ok(serde_json::from_str(read_response(res)?.as_str())?)
+    }
+}
+
+/// The async counterpart of `MessageService`.
+///
+/// Independent requests, like the reactions of many messages, run concurrently but never more
+/// than `max_concurrency` at once, to stay clear of Discord's rate limits.
+pub struct AsyncMessageService<C: AsyncGetMsgs + AsyncSendMsgs> {
+    pub client: C,
+    max_concurrency: usize,
+}
+
+impl<C: AsyncGetMsgs + AsyncSendMsgs> AsyncMessageService<C> {
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            max_concurrency: 4,
+        }
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// See `MessageService::get_memes_for_month`.
+    pub async fn get_memes_for_month(
+        &self,
+        channel_id: &str,
+        wanted_month: &DateTime<Utc>,
+    ) -> Result<Vec<Message>> {
+        let period = Period::month_of(wanted_month)?;
+
+        history_stream(
+            &self.client,
+            channel_id,
+            Order::OldestFirst,
+            Some(cursor_at(&period.start)),
+        )
+        .try_take_while(|msg| future::ready(Ok(msg.timestamp < period.end)))
+        .try_collect()
+        .await
+    }
+
+    /// See `MessageService::get_memes_for_channels`.
+    pub async fn get_memes_for_channels(
+        &self,
+        channel_ids: &[String],
+        wanted_month: &DateTime<Utc>,
+    ) -> Result<Vec<Message>> {
+        let pages: Vec<Vec<Message>> = stream::iter(channel_ids)
+            .map(|channel_id| self.get_memes_for_month(channel_id, wanted_month))
+            .buffer_unordered(self.max_concurrency)
+            .try_collect()
+            .await?;
+
+        let mut result: Vec<Message> = pages.into_iter().flatten().collect();
+        result.sort_by_key(|msg| msg.timestamp);
+        Ok(result)
+    }
+
+    /// Retrieves the users that reacted with `emoji` for each of `msgs`, keyed by message id.
+    pub async fn get_reaction_users(
+        &self,
+        msgs: &[Message],
+        emoji: &str,
+    ) -> Result<HashMap<String, Vec<User>>> {
+        stream::iter(msgs)
+            .map(|msg| async move {
+                let users = self.client.get_reaction_users(msg, emoji).await?;
+                Ok::<_, Error>((msg.id.clone(), users))
+            })
+            .buffer_unordered(self.max_concurrency)
+            .try_collect()
+            .await
+    }
+
+    pub async fn send_message(&self, message_body: MessageBody) -> Result<SentMessage> {
+        self.client.send_messages(message_body).await
+    }
+}
@@ -0,0 +1,127 @@
+use std::future::Future;
+
+use reqwest::multipart;
+use reqwest::Client;
+
+use crate::discord_api::retry::RetryPolicy;
+use crate::discord_api::transport::{Body, HttpRequest, HttpResponse, Method};
+use crate::prelude::*;
+
+/// The async counterpart of `Transport`, for use on a tokio runtime.
+pub trait AsyncTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> impl Future<Output = Result<HttpResponse>> + Send;
+}
+
+/// The default async transport, backed by `reqwest::Client`.
+#[derive(Default, Clone)]
+pub struct AsyncReqwestTransport {
+    client: Client,
+}
+
+impl AsyncReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AsyncTransport for AsyncReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let method = match request.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Delete => reqwest::Method::DELETE,
+        };
+
+        let max_body_bytes = request.max_body_bytes;
+        let mut builder = self.client.request(method, request.url.as_str());
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        builder = match request.body {
+            Some(Body::Json(json)) => builder
+                .header("Content-Type", "application/json")
+                .body(json),
+            Some(Body::Multipart(parts)) => {
+                let mut form = multipart::Form::new();
+                for part in parts {
+                    let mut file =
+                        multipart::Part::bytes(part.data).mime_str(&part.content_type)?;
+                    if let Some(filename) = part.filename {
+                        file = file.file_name(filename);
+                    }
+                    form = form.part(part.name, file);
+                }
+                builder.multipart(form)
+            }
+            None => builder,
+        };
+
+        let mut res = builder.send().await?;
+        let too_large = |max_bytes| Error::TooLarge {
+            url: request.url.clone(),
+            max_bytes,
+        };
+        if let Some(max_bytes) = max_body_bytes {
+            if res.content_length().is_some_and(|len| len > max_bytes) {
+                return Err(too_large(max_bytes));
+            }
+        }
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                (name.to_string(), value)
+            })
+            .collect();
+        let body = match max_body_bytes {
+            // See `ReqwestTransport`, the length is not trusted.
+            Some(max_bytes) => {
+                let mut body = Vec::new();
+                while let Some(chunk) = res.chunk().await? {
+                    body.extend_from_slice(&chunk);
+                    if body.len() as u64 > max_bytes {
+                        return Err(too_large(max_bytes));
+                    }
+                }
+                body
+            }
+            None => res.bytes().await?.to_vec(),
+        };
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// The async counterpart of `Retrying`, waiting on the tokio timer instead of blocking.
+pub struct AsyncRetrying<T: AsyncTransport> {
+    inner: T,
+    policy: RetryPolicy,
+}
+
+impl<T: AsyncTransport> AsyncRetrying<T> {
+    pub fn new(inner: T, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<T: AsyncTransport> AsyncTransport for AsyncRetrying<T> {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let mut attempt = 1;
+        loop {
+            let result = self.inner.send(request.clone()).await;
+            match self.policy.next_delay(&request, &result, attempt) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return result,
+            }
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use log::debug;
+
+use crate::discord_api::messages::{GetMsgs, MessageGetter, MessageService, SendMsgs};
+use crate::discord_api::transport::Transport;
+use crate::models::discord::{Message, RefreshUrlsRequest, RefreshUrlsResponse};
+use crate::prelude::*;
+use crate::utils::cdn_expiry::is_expired;
+use crate::utils::clock::Clock;
+
+/// How many links Discord refreshes per request.
+const REFRESH_BATCH_SIZE: usize = 50;
+
+/// Trait for renewing expired CDN attachment links.
+pub trait RefreshUrls {
+    /// Asks Discord to sign `urls` again, returns the fresh link for each original one.
+    fn refresh_urls(&self, urls: &[String]) -> Result<HashMap<String, String>>;
+}
+
+impl<T: Transport> RefreshUrls for MessageGetter<T> {
+    fn refresh_urls(&self, urls: &[String]) -> Result<HashMap<String, String>> {
+        let mut refreshed = HashMap::new();
+        for batch in urls.chunks(REFRESH_BATCH_SIZE) {
+            let body = RefreshUrlsRequest {
+                attachment_urls: batch.to_vec(),
+            };
+            let res: RefreshUrlsResponse = self.post_json("/attachments/refresh-urls", &body)?;
+            refreshed.extend(
+                res.refreshed_urls
+                    .into_iter()
+                    .map(|url| (url.original, url.refreshed)),
+            );
+        }
+        Ok(refreshed)
+    }
+}
+
+impl<C: GetMsgs + SendMsgs + RefreshUrls> MessageService<C> {
+    /// Replaces the expired CDN links of `msgs`, attachments and embeds alike, by fresh ones.
+    ///
+    /// Only links that expired according to `clock` are sent to Discord, so freshly fetched
+    /// messages cost no request.
+    pub fn refresh_expired_urls(&self, msgs: &mut [Message], clock: &impl Clock) -> Result<()> {
+        let mut expired: Vec<String> = msgs
+            .iter_mut()
+            .flat_map(media_urls_mut)
+            .filter(|url| is_expired(url, clock))
+            .map(|url| url.clone())
+            .collect();
+        expired.sort();
+        expired.dedup();
+        if expired.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Refreshing {} expired links", expired.len());
+        let refreshed = self.client.refresh_urls(&expired)?;
+        for url in msgs.iter_mut().flat_map(media_urls_mut) {
+            if let Some(fresh) = refreshed.get(url.as_str()) {
+                *url = fresh.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Every link to media `msg` carries, the ones Discord may have signed among them.
+fn media_urls_mut(msg: &mut Message) -> Vec<&mut String> {
+    let mut urls: Vec<&mut String> = Vec::new();
+    for attachment in &mut msg.attachments {
+        urls.push(&mut attachment.url);
+        urls.push(&mut attachment.proxy_url);
+    }
+    for embed in &mut msg.embeds {
+        urls.extend(embed.image.as_mut().map(|image| &mut image.url));
+        urls.extend(embed.thumbnail.as_mut().map(|thumbnail| &mut thumbnail.url));
+        urls.extend(embed.video.as_mut().and_then(|video| video.url.as_mut()));
+    }
+    urls
+}
@@ -0,0 +1,96 @@
+use log::{debug, info, warn};
+
+use crate::discord_api::actions::MessageActions;
+use crate::discord_api::messages::{GetMsgs, MessageGetter, MessageService, SendMsgs};
+use crate::discord_api::threads::GetThreads;
+use crate::discord_api::transport::Transport;
+use crate::models::discord::{Channel, ModifyChannelRequest, SentMessage};
+use crate::prelude::*;
+
+/// Discord refuses longer channel topics.
+pub const MAX_TOPIC_LENGTH: usize = 1024;
+
+/// Trait for editing channels.
+pub trait EditChannels {
+    /// Sets the topic of `channel_id`, answering with the updated channel.
+    fn set_channel_topic(&self, channel_id: &str, topic: &str) -> Result<Channel>;
+}
+
+impl<T: Transport> EditChannels for MessageGetter<T> {
+    fn set_channel_topic(&self, channel_id: &str, topic: &str) -> Result<Channel> {
+        let body = ModifyChannelRequest {
+            topic: Some(topic.to_string()),
+        };
+        self.patch_json(format!("/channels/{channel_id}").as_str(), &body)
+    }
+}
+
+impl<C: GetMsgs + SendMsgs + GetThreads + EditChannels> MessageService<C> {
+    /// Sets the topic of `channel_id` to `topic`, cut to Discord's limit.
+    ///
+    /// Discord allows only two edits of a channel per ten minutes, so the topic is only changed
+    /// if it differs. Running into the limit leaves the old topic with a warning instead of
+    /// failing, the edit is not retried if the limit lasts longer than the retry policy waits.
+    /// Returns whether the topic was changed.
+    pub fn update_topic(&self, channel_id: &str, topic: &str) -> Result<bool> {
+        let topic: String = topic.chars().take(MAX_TOPIC_LENGTH).collect();
+        let channel = self.client.get_channel(channel_id)?;
+        if channel.topic.as_deref() == Some(topic.as_str()) {
+            info!("The topic of {channel_id} is up to date");
+            return Ok(false);
+        }
+
+        match self.client.set_channel_topic(channel_id, topic.as_str()) {
+            Ok(_) => {
+                info!("Set the topic of {channel_id} to {topic}");
+                Ok(true)
+            }
+            Err(Error::RateLimited { retry_after, .. }) => {
+                warn!("Editing {channel_id} is rate limited for {retry_after}s, topic unchanged");
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<C: GetMsgs + SendMsgs + GetThreads + MessageActions> MessageService<C> {
+    /// Crossposts `sent` to the servers following its channel, if that is an announcement
+    /// channel. Returns how many messages were published.
+    ///
+    /// Discord allows only ten crossposts per channel and hour, once rate limited the remaining
+    /// messages are left unpublished with a warning.
+    pub fn publish(&self, sent: &[SentMessage]) -> Result<usize> {
+        let Some(first) = sent.first() else {
+            return Ok(0);
+        };
+        let channel = self.client.get_channel(first.channel_id.as_str())?;
+        if !channel.is_announcement() {
+            debug!(
+                "{} is no announcement channel, nothing to publish",
+                channel.id
+            );
+            return Ok(0);
+        }
+
+        let mut published = 0;
+        for msg in sent {
+            match self
+                .client
+                .crosspost_message(msg.channel_id.as_str(), msg.id.as_str())
+            {
+                Ok(()) => published += 1,
+                Err(Error::RateLimited { retry_after, .. }) => {
+                    warn!(
+                        "Publishing is rate limited for {retry_after}s, {} messages unpublished",
+                        sent.len() - published
+                    );
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        info!("Published {published} messages in {}", channel.id);
+        Ok(published)
+    }
+}
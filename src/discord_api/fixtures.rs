@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::discord_api::transport::{HttpRequest, HttpResponse, Method, Transport};
+use crate::prelude::*;
+
+/// A recorded Discord response.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Fixture {
+    pub method: String,
+    /// Path and query relative to the API base url, e.g. `/channels/1/messages?limit=100`
+    pub path: String,
+    pub status: u16,
+    pub body: Value,
+}
+
+/// Returns the file name a fixture for `method` and `path` is stored under.
+pub fn fixture_file_name(method: &str, path: &str) -> String {
+    let key: String = format!("{method}{path}")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{key}.json")
+}
+
+/// Writes sanitized responses into a fixture directory.
+///
+/// Tokens, user names, avatars, free texts like message contents and file names, and signed
+/// CDN parameters are scrubbed before anything is written. User ids are replaced by stable
+/// fake snowflakes, so mentions and authors still line up across all responses recorded by the
+/// same `Recorder`.
+pub struct Recorder {
+    dir: PathBuf,
+    token: String,
+    users: Mutex<HashMap<String, usize>>,
+}
+
+impl Recorder {
+    pub fn new(dir: impl Into<PathBuf>, token: &str) -> Self {
+        Self {
+            dir: dir.into(),
+            token: token.to_string(),
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, method: &str, path: &str, status: u16, body: &str) -> Result<PathBuf> {
+        let body: Value = serde_json::from_str(body)?;
+        let fixture = Fixture {
+            method: method.to_string(),
+            path: path.to_string(),
+            status,
+            body: self.sanitize(body),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let file = self.dir.join(fixture_file_name(method, path));
+        fs::write(&file, serde_json::to_string_pretty(&fixture)?)?;
+        debug!("Recorded {method} {path} to {:?}", file);
+        Ok(file)
+    }
+
+    /// Scrubs the token and personal data from a response body.
+    pub fn sanitize(&self, body: Value) -> Value {
+        let mut users = self.users.lock().unwrap();
+        let mut body = body;
+        collect_users(&body, &mut users);
+        scrub(&mut body, &users, self.token.as_str());
+        body
+    }
+}
+
+/// Fake snowflake for the `idx`th user seen by a recorder.
+fn fake_user_id(idx: usize) -> String {
+    (100_000_000_000_000_000u64 + idx as u64).to_string()
+}
+
+fn is_user(map: &serde_json::Map<String, Value>) -> bool {
+    map.contains_key("username") && map.contains_key("id")
+}
+
+fn collect_users(value: &Value, users: &mut HashMap<String, usize>) {
+    match value {
+        Value::Object(map) => {
+            if is_user(map) {
+                if let Some(id) = map["id"].as_str() {
+                    let next = users.len() + 1;
+                    users.entry(id.to_string()).or_insert(next);
+                }
+            }
+            map.values().for_each(|value| collect_users(value, users));
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_users(value, users)),
+        _ => {}
+    }
+}
+
+fn scrub(value: &mut Value, users: &HashMap<String, usize>, token: &str) {
+    match value {
+        Value::Object(map) => {
+            if is_user(map) {
+                let idx = map["id"].as_str().and_then(|id| users.get(id)).copied();
+                let idx = idx.unwrap_or(0);
+                map.insert("id".into(), Value::String(fake_user_id(idx)));
+                map.insert("username".into(), Value::String(format!("user{idx}")));
+                if map.get("global_name").is_some_and(|name| !name.is_null()) {
+                    map.insert("global_name".into(), Value::String(format!("User {idx}")));
+                }
+                for key in [
+                    "avatar",
+                    "banner",
+                    "avatar_decoration_data",
+                    "email",
+                    "phone",
+                ] {
+                    if map.contains_key(key) {
+                        map.insert(key.into(), Value::Null);
+                    }
+                }
+            }
+            if map.get("nick").is_some_and(|nick| !nick.is_null()) {
+                map.insert("nick".into(), Value::Null);
+            }
+            scrub_file_name(map);
+            for key in FREE_TEXT_KEYS {
+                if let Some(Value::String(text)) = map.get_mut(key) {
+                    *text = redact(text);
+                }
+            }
+            map.values_mut()
+                .for_each(|value| scrub(value, users, token));
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| scrub(value, users, token)),
+        Value::String(text) => *text = scrub_text(text, users, token),
+        _ => {}
+    }
+}
+
+/// Fields users write freely, only the mentions in them are kept.
+const FREE_TEXT_KEYS: [&str; 4] = ["content", "title", "description", "topic"];
+
+/// Keeps the mentions of `text`, e.g. to check who a message pings, and drops everything else.
+fn redact(text: &str) -> String {
+    let mut kept: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| word.starts_with("<@") || word.starts_with("<#"))
+        .collect();
+    if kept.len() < text.split_whitespace().count() {
+        kept.push("[redacted]");
+    }
+    kept.join(" ")
+}
+
+/// Renames an attachment after its id, keeping the extension its media type is guessed from,
+/// also in its urls.
+fn scrub_file_name(map: &mut serde_json::Map<String, Value>) {
+    let (Some(Value::String(name)), Some(Value::String(id))) = (map.get("filename"), map.get("id"))
+    else {
+        return;
+    };
+    let scrubbed = match name.rsplit_once('.') {
+        Some((_, extension)) => format!("{id}.{extension}"),
+        None => id.clone(),
+    };
+    let name = format!("/{name}");
+    for key in ["url", "proxy_url"] {
+        if let Some(Value::String(url)) = map.get_mut(key) {
+            *url = url.replace(name.as_str(), format!("/{scrubbed}").as_str());
+        }
+    }
+    map.insert("filename".into(), Value::String(scrubbed));
+}
+
+fn scrub_text(text: &str, users: &HashMap<String, usize>, token: &str) -> String {
+    let mut text = text.to_string();
+    if !token.is_empty() {
+        text = text.replace(token, "<token>");
+    }
+    if let Some(idx) = users.get(text.as_str()) {
+        return fake_user_id(*idx);
+    }
+    for (id, idx) in users {
+        for mention in ["<@", "<@!"] {
+            text = text.replace(
+                format!("{mention}{id}>").as_str(),
+                format!("{mention}{}>", fake_user_id(*idx)).as_str(),
+            );
+        }
+    }
+    // CDN links are signed, the signature grants access to the file.
+    let mut from = 0;
+    while let Some(found) = text[from..].find("hm=") {
+        let start = from + found + 3;
+        let end = text[start..]
+            .find('&')
+            .map_or(text.len(), |end| start + end);
+        text.replace_range(start..end, "0".repeat(end - start).as_str());
+        from = end;
+    }
+    text
+}
+
+/// Transport middleware that records every successful GET of the API through a `Recorder`.
+///
+/// Downloads from other hosts, like the CDN, pass through unrecorded.
+pub struct Recording<T: Transport> {
+    inner: T,
+    recorder: Recorder,
+    base_url: String,
+}
+
+impl<T: Transport> Recording<T> {
+    pub fn new(inner: T, recorder: Recorder, base_url: &str) -> Self {
+        Self {
+            inner,
+            recorder,
+            base_url: base_url.to_string(),
+        }
+    }
+}
+
+impl<T: Transport> Transport for Recording<T> {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let method = request.method;
+        let is_api = request.url.starts_with(self.base_url.as_str());
+        let path = relative_path(request.url.as_str(), self.base_url.as_str());
+        let res = self.inner.send(request)?;
+
+        if method == Method::Get && is_api && res.is_success() {
+            self.recorder
+                .record("GET", path.as_str(), res.status, res.text().as_str())?;
+        }
+        Ok(res)
+    }
+}
+
+/// Serves recorded fixtures instead of talking to Discord.
+///
+/// GETs are looked up by the exact path `MessageGetter` requests and answer 404 if nothing was
+/// recorded. Everything else succeeds without side effects, so a whole announcement run can be
+/// replayed offline.
+pub struct Replayer {
+    dir: PathBuf,
+    base_url: String,
+}
+
+impl Replayer {
+    pub fn new(dir: impl AsRef<Path>, base_url: &str) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    pub fn load(&self, method: &str, path: &str) -> Result<Fixture> {
+        let file = self.dir.join(fixture_file_name(method, path));
+        let fixture = fs::read_to_string(&file)
+            .map_err(|_| Error::Generic(format!("No fixture for {method} {path}")))?;
+        Ok(serde_json::from_str(fixture.as_str())?)
+    }
+}
+
+impl Transport for Replayer {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let path = relative_path(request.url.as_str(), self.base_url.as_str());
+        if request.method != Method::Get {
+            debug!("Replaying, not sending {} {}", request.method, path);
+            return Ok(HttpResponse {
+                status: 200,
+                headers: vec![],
+                body: b"{}".to_vec(),
+            });
+        }
+
+        let (status, body) = match self.load("GET", path.as_str()) {
+            Ok(fixture) => (fixture.status, fixture.body),
+            Err(_) => (404, json!({ "code": 0, "message": "No fixture recorded" })),
+        };
+        Ok(HttpResponse {
+            status,
+            headers: vec![],
+            body: body.to_string().into_bytes(),
+        })
+    }
+}
+
+fn relative_path(url: &str, base_url: &str) -> String {
+    url.strip_prefix(base_url).unwrap_or(url).to_string()
+}
@@ -0,0 +1,171 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream};
+
+use crate::discord_api::async_messages::AsyncGetMsgs;
+use crate::discord_api::messages::GetMsgs;
+use crate::models::discord::Message;
+use crate::prelude::*;
+use crate::utils::snowflake::snowflake_from_timestamp;
+
+/// Discord's maximum page size for channel history.
+pub const PAGE_SIZE: i32 = 100;
+
+/// The direction channel history is walked in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// From the cursor back in time, using `before`
+    NewestFirst,
+    /// From the cursor forward in time, using `after`
+    OldestFirst,
+}
+
+/// The paging state shared by `MessageHistory` and `history_stream`.
+struct Pager {
+    channel_id: String,
+    order: Order,
+    /// Message ID the next page starts at, exclusive
+    cursor: Option<String>,
+    page_size: i32,
+    buffer: VecDeque<Message>,
+    done: bool,
+}
+
+impl Pager {
+    fn new(channel_id: &str, order: Order, cursor: Option<String>) -> Self {
+        Self {
+            channel_id: channel_id.to_string(),
+            order,
+            cursor,
+            page_size: PAGE_SIZE,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Queues a freshly fetched page in walking order and moves the cursor past it.
+    fn push_page(&mut self, mut page: Vec<Message>) {
+        if page.is_empty() {
+            self.done = true;
+            return;
+        }
+
+        match self.order {
+            Order::NewestFirst => page.sort_by_key(|msg| Reverse(msg.timestamp)),
+            Order::OldestFirst => page.sort_by_key(|msg| msg.timestamp),
+        }
+        self.cursor = page.last().map(|msg| msg.id.clone());
+        self.done = page.len() < self.page_size as usize;
+        self.buffer.extend(page);
+    }
+}
+
+/// Lazily walks the history of the channel `channel_id`, fetching a page through `GetMsgs` only once the
+/// previous one has been consumed.
+///
+/// Combine with `take_while` to stop at a period boundary without requesting further pages:
+///
+/// ```ignore
+/// MessageHistory::since(&client, channel_id, &period.start)
+///     .take_while(|msg| msg.as_ref().map_or(true, |msg| msg.timestamp < period.end))
+/// ```
+pub struct MessageHistory<'a, C: GetMsgs> {
+    client: &'a C,
+    pager: Pager,
+}
+
+impl<'a, C: GetMsgs> MessageHistory<'a, C> {
+    /// Walks the history in `order`, starting right after (or before) the message ID `cursor`.
+    ///
+    /// Without a cursor, `NewestFirst` starts at the newest message of the channel and
+    /// `OldestFirst` at the very first one.
+    pub fn new(client: &'a C, channel_id: &str, order: Order, cursor: Option<String>) -> Self {
+        Self {
+            client,
+            pager: Pager::new(channel_id, order, cursor),
+        }
+    }
+
+    /// Newest to oldest, starting at the newest message of the channel.
+    pub fn newest_first(client: &'a C, channel_id: &str) -> Self {
+        Self::new(client, channel_id, Order::NewestFirst, None)
+    }
+
+    /// Oldest to newest, starting at the first message posted at or after `start`.
+    pub fn since(client: &'a C, channel_id: &str, start: &DateTime<Utc>) -> Self {
+        Self::new(
+            client,
+            channel_id,
+            Order::OldestFirst,
+            Some(cursor_at(start)),
+        )
+    }
+
+    pub fn page_size(mut self, page_size: i32) -> Self {
+        self.pager.page_size = page_size.clamp(1, PAGE_SIZE);
+        self
+    }
+}
+
+impl<C: GetMsgs> Iterator for MessageHistory<'_, C> {
+    type Item = Result<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pager.buffer.is_empty() && !self.pager.done {
+            let cursor = self.pager.cursor.clone();
+            let limit = Some(self.pager.page_size);
+            let channel_id = self.pager.channel_id.as_str();
+            let page = match self.pager.order {
+                Order::NewestFirst => self.client.get_messages_before(channel_id, cursor, limit),
+                Order::OldestFirst => self.client.get_messages(channel_id, cursor, limit),
+            };
+            match page {
+                Ok(page) => self.pager.push_page(page),
+                Err(err) => {
+                    self.pager.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        self.pager.buffer.pop_front().map(Ok)
+    }
+}
+
+/// The async counterpart of `MessageHistory`, yielding messages as a `Stream`.
+pub fn history_stream<'a, C: AsyncGetMsgs>(
+    client: &'a C,
+    channel_id: &str,
+    order: Order,
+    cursor: Option<String>,
+) -> impl Stream<Item = Result<Message>> + 'a {
+    let pager = Pager::new(channel_id, order, cursor);
+    stream::unfold(pager, move |mut pager| async move {
+        if pager.buffer.is_empty() && !pager.done {
+            let cursor = pager.cursor.clone();
+            let limit = Some(pager.page_size);
+            let channel_id = pager.channel_id.as_str();
+            let page = match pager.order {
+                Order::NewestFirst => client.get_messages_before(channel_id, cursor, limit).await,
+                Order::OldestFirst => client.get_messages(channel_id, cursor, limit).await,
+            };
+            match page {
+                Ok(page) => pager.push_page(page),
+                Err(err) => {
+                    pager.done = true;
+                    return Some((Err(err), pager));
+                }
+            }
+        }
+        pager.buffer.pop_front().map(|msg| (Ok(msg), pager))
+    })
+}
+
+/// The `after` cursor that makes history start at the first message posted at or after `start`.
+pub fn cursor_at(start: &DateTime<Utc>) -> String {
+    // `after` is exclusive, so start right before the first possible snowflake.
+    snowflake_from_timestamp(start)
+        .saturating_sub(1)
+        .to_string()
+}
@@ -1,35 +1,41 @@
 use std::ops::DerefMut;
 use std::slice::Iter;
 use std::thread;
-use std::time::Duration;
 use std::vec::IntoIter;
 
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
 use log::{debug, log, warn};
-use reqwest::blocking::Client;
-use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
+use crate::discord_api::fixtures::{Recorder, Recording};
+use crate::discord_api::history::MessageHistory;
 use crate::discord_api::request::*;
+use crate::discord_api::retry::{RetryPolicy, Retrying};
+use crate::discord_api::transport::{ReqwestTransport, Transport};
+use crate::discord_api::uploads::{multipart_parts, FileUpload};
 use crate::error::Error::{Generic, Static};
-use crate::models::discord::{Message, MessageBody};
+use crate::models::discord::{Message, MessageBody, SentMessage};
 use crate::prelude::*;
 use crate::utils::config::Config;
+use crate::utils::period::Period;
 
 pub trait GetMsgs {
     /// Trait for retrieving messages.
     ///
     /// This trait provides a method `get_messages` that can be implemented by types
-    /// to retrieve a list of messages. The method takes two optional parameters:
-    /// `after` and `_limit`. The `after` parameter is used to specify a message ID
-    /// to start retrieving messages after, and the `_limit` parameter is used to
+    /// to retrieve a list of messages from the channel `channel_id`. The method takes two
+    /// optional parameters: `after` and `_limit`. The `after` parameter is used to specify a
+    /// message ID to start retrieving messages after, and the `_limit` parameter is used to
     /// limit the number of messages to retrieve.
     ///
     /// # Arguments
     ///
+    /// - `channel_id` - The channel to retrieve messages from.
     /// - `after` - An optional string representing the message ID to start retrieving
-    ///             messages after.
+    ///   messages after.
     /// - `limit` - An optional integer representing the maximum number of messages to
-    ///              retrieve.
+    ///   retrieve.
     ///
     /// # Returns
     ///
@@ -41,29 +47,166 @@ pub trait GetMsgs {
     /// Implement the GetMsgs trait for a custom type
     /// struct MyType;
     /// impl GetMsgs for MyType {
-    /// fn get_messages(&self, after: Option<&str>, _limit: Option<i32>) -> Result<Vec<Message>> {
+    /// fn get_messages(&self, channel_id: &str, after: Option<&str>, _limit: Option<i32>) -> Result<Vec<Message>> {
     /// //Implementation goes here
     ///     }
     /// }
+    fn get_messages(
+        &self,
+        channel_id: &str,
+        after: Option<String>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Message>>;
 
-    fn get_messages(&self, after: Option<String>, limit: Option<i32>) -> Result<Vec<Message>>;
+    /// Like `get_messages`, but retrieves the messages right before the message ID `before`,
+    /// or the newest messages of the channel if `before` is `None`.
+    fn get_messages_before(
+        &self,
+        channel_id: &str,
+        before: Option<String>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Message>>;
 }
 pub trait SendMsgs {
-    fn send_messages(&self, message_body: MessageBody) -> Result<()>;
+    fn send_messages(&self, message_body: MessageBody) -> Result<SentMessage>;
+
+    /// Sends `message_body` as `multipart/form-data` with `files` attached.
+    fn send_messages_with_files(
+        &self,
+        message_body: MessageBody,
+        files: Vec<FileUpload>,
+    ) -> Result<SentMessage>;
+}
+/// Path and query of a channel history request, relative to the API base url.
+pub fn messages_path(
+    channel_id: &str,
+    after: Option<&str>,
+    before: Option<&str>,
+    limit: Option<i32>,
+) -> String {
+    let mut path = format!("/channels/{channel_id}/messages?");
+    if let Some(msg_id) = after {
+        path.push_str(format!("after={msg_id}&").as_str())
+    }
+    if let Some(msg_id) = before {
+        path.push_str(format!("before={msg_id}&").as_str())
+    }
+    if let Some(limit) = limit {
+        path.push_str(format!("limit={}", limit).as_str());
+    }
+    path.trim_end_matches(['?', '&']).to_string()
 }
-pub struct MessageGetter {
-    client: Client,
+
+pub struct MessageGetter<T: Transport = Box<dyn Transport + Send + Sync>> {
+    pub(crate) transport: T,
+    base_url: String,
+    token: String,
+    out_channel_id: String,
 }
 
 impl MessageGetter {
     pub fn new() -> Self {
+        Self::from_config(Config::get())
+    }
+
+    /// Creates a `MessageGetter` talking to `config.base_url`, e.g. a local mock server.
+    ///
+    /// Uses reqwest with the configured `RetryPolicy`, recording responses if
+    /// `config.record_fixtures` is set.
+    pub fn from_config(config: &Config) -> Self {
+        let retry_policy = RetryPolicy::from_config(config);
+        let mut transport: Box<dyn Transport + Send + Sync> =
+            Box::new(Retrying::new(ReqwestTransport::new(), retry_policy));
+        if let Some(dir) = &config.record_fixtures {
+            let recorder = Recorder::new(dir, config.token.as_str());
+            transport = Box::new(Recording::new(
+                transport,
+                recorder,
+                config.base_url.as_str(),
+            ));
+        }
+        Self::with_transport(config, transport)
+    }
+}
+
+impl<T: Transport> MessageGetter<T> {
+    pub fn with_transport(config: &Config, transport: T) -> Self {
         Self {
-            client: Client::new(),
+            transport,
+            base_url: config.base_url.clone(),
+            token: config.token.clone(),
+            out_channel_id: config.out_channel_id.clone(),
         }
     }
 }
 
-impl GetMsgs for MessageGetter {
+impl<T: Transport> MessageGetter<T> {
+    /// GETs `path`, relative to the API base url, and parses the answer.
+    pub(crate) fn get_json<D: DeserializeOwned>(&self, path: &str) -> Result<D> {
+        let url = format!("{}{}", self.base_url, path);
+        debug!("{:#?}", url);
+
+        let body = request(&self.transport, self.token.as_str(), url.as_str(), false)?;
+
+        Ok(serde_json::from_str(body.as_str())?)
+    }
+
+    /// POSTs `body` as JSON to `path`, relative to the API base url, and parses the answer.
+    pub(crate) fn post_json<B: Serialize, D: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<D> {
+        let url = format!("{}{}", self.base_url, path);
+        let res = self.transport.post(
+            url.as_str(),
+            discord_headers(self.token.as_str()),
+            serde_json::to_string(body)?,
+        )?;
+        Ok(serde_json::from_str(read_response(res)?.as_str())?)
+    }
+
+    /// PATCHes `path`, relative to the API base url, with `body` as JSON and parses the answer.
+    pub(crate) fn patch_json<B: Serialize, D: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<D> {
+        let url = format!("{}{}", self.base_url, path);
+        let res = self.transport.patch(
+            url.as_str(),
+            discord_headers(self.token.as_str()),
+            serde_json::to_string(body)?,
+        )?;
+        Ok(serde_json::from_str(read_response(res)?.as_str())?)
+    }
+
+    /// PUTs to `path`, relative to the API base url, e.g. to add a reaction.
+    pub(crate) fn put(&self, path: &str) -> Result<()> {
+        let url = format!("{}{}", self.base_url, path);
+        let res = self
+            .transport
+            .put(url.as_str(), discord_headers(self.token.as_str()))?;
+        read_response(res)?;
+        Ok(())
+    }
+
+    /// DELETEs `path`, relative to the API base url, e.g. to remove a pin.
+    pub(crate) fn delete(&self, path: &str) -> Result<()> {
+        let url = format!("{}{}", self.base_url, path);
+        let res = self
+            .transport
+            .delete(url.as_str(), discord_headers(self.token.as_str()))?;
+        read_response(res)?;
+        Ok(())
+    }
+
+    fn fetch_messages(&self, path: &str) -> Result<Vec<Message>> {
+        self.get_json(path)
+    }
+}
+
+impl<T: Transport> GetMsgs for MessageGetter<T> {
     /// Implements the `GetMsgs` trait for the `MessageGetter` struct.
     ///
     /// This implementation provides a method `get_messages` that retrieves messages from a Discord channel.
@@ -77,58 +220,62 @@ impl GetMsgs for MessageGetter {
     ///
     /// A `Result` containing a vector of `Message` structs if successful, or an `Error` if an error occurs.
     ///
-    fn get_messages(&self, after: Option<String>, _limit: Option<i32>) -> Result<Vec<Message>> {
+    fn get_messages(
+        &self,
+        channel_id: &str,
+        after: Option<String>,
+        _limit: Option<i32>,
+    ) -> Result<Vec<Message>> {
         let limit = _limit.unwrap_or(1);
-        let config: &Config = Config::get();
-        let mut url: String = format!(
-            "{}/channels/{}/messages?",
-            config.base_url, config.in_channel_id
-        );
-
-        if after.is_some() {
-            let msg_id = after.as_ref().unwrap().clone();
-            url.push_str(format!("after={msg_id}&").as_str())
-        }
-        url.push_str(format!("limit={}", limit.to_string()).as_str());
-        println!("{:#?}", url);
-
-        let body = request(&self.client, config.token.as_str(), url.as_str(), false)?;
+        let path = messages_path(channel_id, after.as_deref(), None, Some(limit));
+        self.fetch_messages(path.as_str())
+    }
 
-        if body.contains("rate limited") {
-            warn!("Get messages was rate limited!");
-            debug!("GET Messages was rate limited!");
-            thread::sleep(Duration::from_secs(1));
-            return self.get_messages(after, _limit);
-        }
+    fn get_messages_before(
+        &self,
+        channel_id: &str,
+        before: Option<String>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Message>> {
+        let limit = limit.unwrap_or(1);
+        let path = messages_path(channel_id, None, before.as_deref(), Some(limit));
+        self.fetch_messages(path.as_str())
+    }
+}
 
-        let msgs: Vec<Message> = serde_json::from_str(body.as_str())
-            .expect(format!("Could not parse Message:\n Body:\n {}", body.as_str()).as_str());
+impl<T: Transport> SendMsgs for MessageGetter<T> {
+    fn send_messages(&self, message_body: MessageBody) -> Result<SentMessage> {
+        let url: String = format!(
+            "{}/channels/{}/messages",
+            self.base_url, self.out_channel_id
+        );
 
-        Ok(msgs)
+        let res = self.transport.post(
+            url.as_str(),
+            discord_headers(self.token.as_str()),
+            serde_json::to_string(&message_body)?,
+        )?;
+        debug!("{:#?}", res.status);
+        Ok(serde_json::from_str(read_response(res)?.as_str())?)
     }
-}
 
-impl SendMsgs for MessageGetter {
-    fn send_messages(&self, message_body: MessageBody) -> Result<()> {
-        let config: &Config = Config::get();
-        let mut url: String = format!(
+    fn send_messages_with_files(
+        &self,
+        message_body: MessageBody,
+        files: Vec<FileUpload>,
+    ) -> Result<SentMessage> {
+        let url: String = format!(
             "{}/channels/{}/messages",
-            config.base_url, config.out_channel_id
+            self.base_url, self.out_channel_id
         );
 
-        let req = self
-            .client
-            .post(url)
-            .header("Authorization", config.token.as_str())
-            .header(CONTENT_TYPE, "application/json")
-            .header(ACCEPT, "application/json")
-            .body(serde_json::to_string(&message_body).unwrap())
-            .build()?;
-
-        let res = self.client.execute(req)?;
-        println!("{:#?}", res.status());
-        println!("{:#?}", res.text());
-        Ok(())
+        let res = self.transport.multipart(
+            url.as_str(),
+            discord_headers(self.token.as_str()),
+            multipart_parts(message_body, files)?,
+        )?;
+        debug!("{:#?}", res.status);
+        Ok(serde_json::from_str(read_response(res)?.as_str())?)
     }
 }
 
@@ -141,23 +288,25 @@ impl<C: GetMsgs + SendMsgs> MessageService<C> {
         Self { client }
     }
 
-    pub fn get_memes_for_month(&self, wanted_month: &DateTime<Utc>) -> Vec<Message> {
-        /// This is a method called `get_memes_for_month` belonging to a struct `MessageService`.
-        /// It takes a reference to a `DateTime<Utc>` called `wanted_month` as input.
-        /// The method retrieves messages from the `client` using the `get_messages` method.
-        /// It sorts the messages by timestamp and filters them based on the `wanted_month`.
-        /// The filtered messages are then returned as a `Vec<Message>`.
-        /// The method continues retrieving messages until it encounters a timestamp older than the `wanted_month`.
-        /// Note: The code snippet assumes the existence of a `debug!` macro for logging purposes.
+    pub fn get_memes_for_month(
+        &self,
+        channel_id: &str,
+        wanted_month: &DateTime<Utc>,
+    ) -> Result<Vec<Message>> {
+        /// Retrieves all messages posted to `channel_id` in the month of `wanted_month`.
+        ///
+        /// Walks the channel history oldest first from the start of the month and stops at the
+        /// first message of the following month, so no further pages are requested.
         ///
         /// # Arguments
         ///
         /// * `self` - The `MessageService` instance.
+        /// * `channel_id` - The channel to retrieve the messages from.
         /// * `wanted_month` - The desired month to filter the messages.
         ///
         /// # Returns
         ///
-        /// A `Vec<Message>` containing the filtered messages for the desired month.
+        /// The messages of the desired month, oldest first.
         ///
         /// # Example
         ///
@@ -165,67 +314,61 @@ impl<C: GetMsgs + SendMsgs> MessageService<C> {
         /// use your_crate::MessageService;
         /// let service = MessageService::new();
         /// let wanted_month = DateTime::parse_from_rfc3339("2022-01-01T00:00:00Z").unwrap();
-        /// let memes = service.get_memes_for_month(&wanted_month);
+        /// let memes = service.get_memes_for_month("795984622399782912", &wanted_month)?;
         /// println!("{:#?}", memes);
-        let mut run = true;
-        let mut result: Vec<Message> = Vec::new();
-
-        let mut last_msg: Option<String> = None;
-
-        while run {
-            let mut page: Vec<Message> = self
-                .client
-                .get_messages(last_msg.clone(), Some(10))
-                .unwrap();
-            if page.is_empty() {
-                run = false;
-
-                break;
-            }
-
-            let oldest_msg: Message = page.first().expect("Should never happen!").clone();
-            page.sort_by(|msg1, msg2| msg1.timestamp.cmp(&msg2.timestamp));
-
-            let page_iter = page.clone().into_iter();
-            let timestamps: Vec<DateTime<Utc>> = page_iter.map(|msg| msg.timestamp).collect();
-
-            timestamps.iter().enumerate().for_each(|(idx, ts)| {
-                println!("{}, {:#?}", idx, ts);
-            });
+        let period = Period::month_of(wanted_month)?;
 
-            let timestamp: DateTime<Utc> = oldest_msg.clone().timestamp;
-
-            debug!("1. {:#?} 2. {:#?}", timestamp, wanted_month);
-            debug!("{}", wanted_month.month());
+        MessageHistory::since(&self.client, channel_id, &period.start)
+            .take_while(|msg| msg.as_ref().map_or(true, |msg| msg.timestamp < period.end))
+            .collect()
+    }
 
-            if timestamp.year() < wanted_month.year()
-                || (timestamp.year() == wanted_month.year()
-                    && timestamp.month() > wanted_month.month())
-            {
-                println!("The timestamp is older than the wanted month");
-                run = false;
-            } else {
-                let id = oldest_msg.clone().id;
-                last_msg = Some(id);
-            }
+    /// Like `get_memes_for_month`, but for several channels at once.
+    ///
+    /// Each channel is fetched on its own thread, the messages of all channels are returned
+    /// oldest first.
+    pub fn get_memes_for_channels(
+        &self,
+        channel_ids: &[String],
+        wanted_month: &DateTime<Utc>,
+    ) -> Result<Vec<Message>>
+    where
+        C: Sync,
+    {
+        let pages: Vec<Result<Vec<Message>>> = thread::scope(|scope| {
+            let handles: Vec<_> = channel_ids
+                .iter()
+                .map(|channel_id| {
+                    scope.spawn(move || self.get_memes_for_month(channel_id, wanted_month))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Channel thread panicked"))
+                .collect()
+        });
 
-            result.append(&mut page);
+        let mut result: Vec<Message> = Vec::new();
+        for page in pages {
+            result.extend(page?);
         }
+        result.sort_by_key(|msg| msg.timestamp);
+        Ok(result)
+    }
 
-        // Filter wanted_month.year() == timestamp.year() && wanted_month.month() == timestamp.month()
-        let filtered_result: Vec<Message> = result
-            .iter()
-            .filter(|msg| {
-                wanted_month.year() == msg.timestamp.year()
-                    && wanted_month.month() == msg.timestamp.month()
-            })
-            .map(|msg| msg.clone())
-            .collect();
-
-        return filtered_result;
+    pub fn send_message(&self, message_body: MessageBody) -> Result<SentMessage> {
+        self.client.send_messages(message_body)
     }
 
-    pub fn send_message(&self, message_body: MessageBody) {
-        self.client.send_messages(message_body);
+    /// Sends `message_body`, as multipart message if there are `files` to attach.
+    pub fn send_message_with_files(
+        &self,
+        message_body: MessageBody,
+        files: Vec<FileUpload>,
+    ) -> Result<SentMessage> {
+        if files.is_empty() {
+            return self.send_message(message_body);
+        }
+        self.client.send_messages_with_files(message_body, files)
     }
 }
@@ -1,2 +1,15 @@
+pub mod actions;
+pub mod async_messages;
+pub mod async_transport;
+pub mod attachments;
+pub mod channels;
+pub mod fixtures;
+pub mod history;
 pub mod messages;
+pub mod pins;
 pub mod request;
+pub mod retry;
+pub mod roles;
+pub mod threads;
+pub mod transport;
+pub mod uploads;
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+
+use crate::discord_api::messages::{GetMsgs, MessageGetter, MessageService, SendMsgs};
+use crate::discord_api::request::UNKNOWN_MESSAGE;
+use crate::discord_api::transport::Transport;
+use crate::models::discord::Message;
+use crate::models::state::PinnedWinner;
+use crate::prelude::*;
+
+/// Discord refuses to pin more messages per channel.
+pub const MAX_PINS: usize = 50;
+
+/// Trait for pinning messages.
+pub trait PinMessages {
+    /// Retrieves the pinned messages of `channel_id`, most recently pinned first.
+    fn get_pinned_messages(&self, channel_id: &str) -> Result<Vec<Message>>;
+
+    fn pin_message(&self, channel_id: &str, message_id: &str) -> Result<()>;
+
+    fn unpin_message(&self, channel_id: &str, message_id: &str) -> Result<()>;
+}
+
+impl<T: Transport> PinMessages for MessageGetter<T> {
+    fn get_pinned_messages(&self, channel_id: &str) -> Result<Vec<Message>> {
+        self.get_json(format!("/channels/{channel_id}/pins").as_str())
+    }
+
+    fn pin_message(&self, channel_id: &str, message_id: &str) -> Result<()> {
+        self.put(format!("/channels/{channel_id}/pins/{message_id}").as_str())
+    }
+
+    fn unpin_message(&self, channel_id: &str, message_id: &str) -> Result<()> {
+        self.delete(format!("/channels/{channel_id}/pins/{message_id}").as_str())
+    }
+}
+
+impl<C: GetMsgs + SendMsgs + PinMessages> MessageService<C> {
+    /// Pins `msg` in its channel and records it in `pinned_winners`.
+    ///
+    /// While the channel has `pin_limit` or more pins, the oldest winner the bot pinned there is
+    /// unpinned first; pins of anyone else are never touched. Winners that were unpinned or
+    /// deleted meanwhile are forgotten.
+    pub fn pin_winner(
+        &self,
+        competition: &str,
+        msg: &Message,
+        pin_limit: usize,
+        pinned_winners: &mut Vec<PinnedWinner>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let channel_id = msg.channel_id.as_str();
+        if msg.pinned {
+            debug!("{} is pinned already", msg.id);
+            return Ok(());
+        }
+        let pins = self.client.get_pinned_messages(channel_id)?;
+        if pins.iter().any(|pin| pin.id == msg.id) {
+            debug!("{} is pinned already", msg.id);
+            return Ok(());
+        }
+        pinned_winners.retain(|winner| {
+            winner.channel_id != channel_id || pins.iter().any(|pin| pin.id == winner.message_id)
+        });
+
+        let mut pin_count = pins.len();
+        while pin_count >= pin_limit.min(MAX_PINS) {
+            let Some(idx) = pinned_winners
+                .iter()
+                .position(|winner| winner.channel_id == channel_id)
+            else {
+                warn!("{channel_id} has {pin_count} pins, none of them a winner to unpin");
+                break;
+            };
+            let oldest = pinned_winners.remove(idx);
+            match self
+                .client
+                .unpin_message(channel_id, oldest.message_id.as_str())
+            {
+                Ok(()) => debug!("Unpinned the winner {}", oldest.message_id),
+                Err(Error::Discord { code, .. }) if code == UNKNOWN_MESSAGE => {}
+                Err(err) => {
+                    pinned_winners.insert(idx, oldest);
+                    return Err(err);
+                }
+            }
+            pin_count -= 1;
+        }
+
+        self.client.pin_message(channel_id, msg.id.as_str())?;
+        pinned_winners.push(PinnedWinner {
+            competition: competition.to_string(),
+            channel_id: channel_id.to_string(),
+            message_id: msg.id.clone(),
+            pinned_at: now,
+        });
+        Ok(())
+    }
+}
@@ -1,23 +1,54 @@
-use std::env;
-use reqwest::{
-    blocking::Client,
-    header::{ACCEPT, CONTENT_TYPE},
-};
-
+use crate::discord_api::transport::{HttpResponse, Transport};
+use crate::models::discord::{ErrorResponse, RateLimitResponse};
 use crate::prelude::*;
 
-pub fn request(client: &Client, token: &str, url: &str, debug: bool) -> Result<String> {
-    let req = client
-        .get(url)
-        .header("Authorization", token)
-        .header(CONTENT_TYPE, "application/json")
-        .header(ACCEPT, "application/json")
-        .build()?;
-    //.map_err(|error: reqwest::Error| Error::Generic(format!("Reqwest err:\n {:#?}", error)))?;
+/// Headers every Discord API request carries.
+pub fn discord_headers(token: &str) -> Vec<(String, String)> {
+    vec![
+        ("Authorization".to_string(), token.to_string()),
+        ("Accept".to_string(), "application/json".to_string()),
+    ]
+}
 
+pub fn request(transport: &impl Transport, token: &str, url: &str, debug: bool) -> Result<String> {
     if debug {
-        println!("{:#?}", req);
+        println!("GET {:#?}", url);
+    }
+
+    read_response(transport.get(url, discord_headers(token))?)
+}
+
+/// Discord's JSON error code for a member that left the guild.
+pub const UNKNOWN_MEMBER: i32 = 10007;
+/// Discord's JSON error code for a deleted message.
+pub const UNKNOWN_MESSAGE: i32 = 10008;
+
+/// Reads the body of a Discord response.
+///
+/// Non-success responses are turned into `Error::RateLimited` or `Error::Discord`, so callers
+/// only ever see the body of successful requests.
+pub fn read_response(res: HttpResponse) -> Result<String> {
+    let body = res.text();
+    if res.is_success() {
+        return Ok(body);
+    }
+
+    if res.status == 429 {
+        if let Ok(rate_limit) = serde_json::from_str::<RateLimitResponse>(body.as_str()) {
+            return Err(Error::RateLimited {
+                retry_after: rate_limit.retry_after,
+                global: rate_limit.global,
+            });
+        }
     }
 
-    Ok(client.execute(req)?.text()?)
+    let envelope = serde_json::from_str::<ErrorResponse>(body.as_str()).unwrap_or(ErrorResponse {
+        code: 0,
+        message: body,
+    });
+    Err(Error::Discord {
+        status: res.status,
+        code: envelope.code,
+        message: envelope.message,
+    })
 }
@@ -0,0 +1,158 @@
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+use rand::Rng;
+
+use crate::discord_api::transport::{HttpRequest, HttpResponse, Transport};
+use crate::models::discord::RateLimitResponse;
+use crate::prelude::*;
+use crate::utils::config::Config;
+
+/// When and how long to wait before repeating a failed Discord request.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one, `1` disables retries
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every further attempt
+    pub base_delay: Duration,
+    /// Upper bound for a single delay, rate limits lasting longer are not waited out
+    pub max_delay: Duration,
+    /// Randomize delays between half and the full backoff, so parallel runs spread out
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_attempts: config.retry_max_attempts.max(1),
+            base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            max_delay: Duration::from_millis(config.retry_max_delay_ms),
+            jitter: true,
+        }
+    }
+
+    /// The backoff before attempt number `attempt + 1`, `attempt` starting at 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+        rand::thread_rng().gen_range(delay / 2..=delay)
+    }
+}
+
+/// Why a request failed in a way that might go away.
+enum Transient {
+    /// Discord did not process the request and asked us to wait.
+    RateLimited(Duration),
+    /// A 5xx answer, the request may or may not have been processed.
+    ServerError(u16),
+    /// The connection could not be established, nothing was sent.
+    Connect,
+    /// The connection broke after the request may have been sent.
+    Network,
+}
+
+/// Transport middleware that repeats failed requests according to a `RetryPolicy`.
+///
+/// 429s and connection failures are always retried since Discord never saw the request. 5xx
+/// answers and broken connections are only retried for idempotent methods, so a POST is never
+/// sent twice.
+pub struct Retrying<T: Transport> {
+    inner: T,
+    policy: RetryPolicy,
+}
+
+impl<T: Transport> Retrying<T> {
+    pub fn new(inner: T, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<T: Transport> Transport for Retrying<T> {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let mut attempt = 1;
+        loop {
+            let result = self.inner.send(request.clone());
+            match self.policy.next_delay(&request, &result, attempt) {
+                Some(delay) => thread::sleep(delay),
+                None => return result,
+            }
+            attempt += 1;
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Decides whether `result` of the `attempt`th try of `request` should be retried.
+    ///
+    /// Returns how long to wait first, or `None` if `result` should be handed to the caller.
+    pub fn next_delay(
+        &self,
+        request: &HttpRequest,
+        result: &Result<HttpResponse>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        let transient = match result {
+            Ok(res) if res.status == 429 => Transient::RateLimited(retry_after(res)),
+            Ok(res) if res.status >= 500 => Transient::ServerError(res.status),
+            Ok(_) => return None,
+            Err(Error::ReqwestError(err)) if err.is_connect() => Transient::Connect,
+            Err(Error::ReqwestError(err)) if err.is_timeout() || err.is_request() => {
+                Transient::Network
+            }
+            Err(_) => return None,
+        };
+
+        let (retryable, delay) = match transient {
+            // Retrying before the limit is over only earns another 429, and enough of them get
+            // the bot banned, so long limits go to the caller as `Error::RateLimited`.
+            Transient::RateLimited(wait) => (wait <= self.max_delay, wait),
+            Transient::Connect => (true, self.backoff(attempt)),
+            Transient::ServerError(_) | Transient::Network => {
+                (request.method.is_idempotent(), self.backoff(attempt))
+            }
+        };
+        if !retryable || attempt >= self.max_attempts {
+            return None;
+        }
+
+        let reason = match transient {
+            Transient::RateLimited(_) => "rate limited".to_string(),
+            Transient::ServerError(status) => format!("status {status}"),
+            Transient::Connect => "connection failed".to_string(),
+            Transient::Network => "network error".to_string(),
+        };
+        warn!(
+            "{} {} failed ({reason}), attempt {attempt}/{}, retrying in {:?}",
+            request.method, request.url, self.max_attempts, delay
+        );
+        Some(delay)
+    }
+}
+
+/// How long Discord asked us to wait, from the body or the `Retry-After` header.
+fn retry_after(res: &HttpResponse) -> Duration {
+    let seconds = serde_json::from_slice::<RateLimitResponse>(&res.body)
+        .map(|rate_limit| rate_limit.retry_after)
+        .ok()
+        .or_else(|| {
+            res.header("Retry-After")
+                .and_then(|value| value.parse().ok())
+        })
+        .unwrap_or(1.0);
+    Duration::from_secs_f64(seconds.max(0.0))
+}
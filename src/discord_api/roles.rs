@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use log::info;
+
+use crate::discord_api::messages::{GetMsgs, MessageGetter, MessageService, SendMsgs};
+use crate::discord_api::request::UNKNOWN_MEMBER;
+use crate::discord_api::transport::Transport;
+use crate::models::state::{AuditEntry, BotState, RoleAction, RoleHolder};
+use crate::prelude::*;
+
+/// Trait for granting and revoking roles of guild members.
+pub trait ManageRoles {
+    fn add_member_role(&self, guild_id: &str, user_id: &str, role_id: &str) -> Result<()>;
+
+    fn remove_member_role(&self, guild_id: &str, user_id: &str, role_id: &str) -> Result<()>;
+}
+
+impl<T: Transport> ManageRoles for MessageGetter<T> {
+    fn add_member_role(&self, guild_id: &str, user_id: &str, role_id: &str) -> Result<()> {
+        self.put(format!("/guilds/{guild_id}/members/{user_id}/roles/{role_id}").as_str())
+    }
+
+    fn remove_member_role(&self, guild_id: &str, user_id: &str, role_id: &str) -> Result<()> {
+        self.delete(format!("/guilds/{guild_id}/members/{user_id}/roles/{role_id}").as_str())
+    }
+}
+
+impl<C: GetMsgs + SendMsgs + ManageRoles> MessageService<C> {
+    /// Passes the winner role `role_id` of `competition` on to `user_id`.
+    ///
+    /// The previous holder recorded in `state` loses the role first, unless they won again.
+    /// Every change is appended to the audit log of `state`.
+    pub fn rotate_winner_role(
+        &self,
+        competition: &str,
+        guild_id: &str,
+        role_id: &str,
+        user_id: &str,
+        state: &mut BotState,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let holder = RoleHolder {
+            competition: competition.to_string(),
+            guild_id: guild_id.to_string(),
+            role_id: role_id.to_string(),
+            user_id: user_id.to_string(),
+        };
+        let previous = state
+            .role_holders
+            .iter()
+            .position(|holder| holder.competition == competition);
+        if let Some(idx) = previous {
+            if state.role_holders[idx] == holder {
+                info!("{user_id} holds the winner role of {competition} already");
+                return Ok(());
+            }
+            let previous = state.role_holders.remove(idx);
+            self.revoke(&previous, state, now)?;
+        }
+
+        self.client.add_member_role(guild_id, user_id, role_id)?;
+        info!("Granted the winner role of {competition} to {user_id}");
+        state
+            .audit_log
+            .push(audit_entry(&holder, RoleAction::Granted, now));
+        state.role_holders.push(holder);
+        Ok(())
+    }
+
+    fn revoke(&self, holder: &RoleHolder, state: &mut BotState, now: DateTime<Utc>) -> Result<()> {
+        match self.client.remove_member_role(
+            holder.guild_id.as_str(),
+            holder.user_id.as_str(),
+            holder.role_id.as_str(),
+        ) {
+            Ok(()) => {}
+            // Members that left have no roles to take.
+            Err(Error::Discord { code, .. }) if code == UNKNOWN_MEMBER => {}
+            Err(err) => {
+                state.role_holders.push(holder.clone());
+                return Err(err);
+            }
+        }
+        info!(
+            "Revoked the winner role of {} from {}",
+            holder.competition, holder.user_id
+        );
+        state
+            .audit_log
+            .push(audit_entry(holder, RoleAction::Revoked, now));
+        Ok(())
+    }
+}
+
+fn audit_entry(holder: &RoleHolder, action: RoleAction, now: DateTime<Utc>) -> AuditEntry {
+    AuditEntry {
+        at: now,
+        competition: holder.competition.clone(),
+        action,
+        guild_id: holder.guild_id.clone(),
+        role_id: holder.role_id.clone(),
+        user_id: holder.user_id.clone(),
+    }
+}
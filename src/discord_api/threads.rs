@@ -0,0 +1,216 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::{debug, warn};
+
+use crate::discord_api::messages::{GetMsgs, MessageGetter, MessageService, SendMsgs};
+use crate::discord_api::request::UNKNOWN_MESSAGE;
+use crate::discord_api::transport::Transport;
+use crate::models::discord::{Channel, Message, ThreadList};
+use crate::prelude::*;
+use crate::utils::competition::Competition;
+use crate::utils::period::Period;
+use crate::utils::snowflake::timestamp_from_snowflake;
+
+/// Discord's maximum page size for archived threads.
+const THREAD_PAGE_SIZE: i32 = 100;
+
+/// Trait for discovering threads and forum posts below a channel.
+pub trait GetThreads {
+    fn get_channel(&self, channel_id: &str) -> Result<Channel>;
+
+    /// Retrieves a single message, e.g. the starter message of a forum post.
+    fn get_message(&self, channel_id: &str, message_id: &str) -> Result<Message>;
+
+    /// Retrieves all active threads of the guild `guild_id`, across all channels.
+    fn get_active_threads(&self, guild_id: &str) -> Result<ThreadList>;
+
+    /// Retrieves the public threads of `channel_id` archived before `before`, most recently
+    /// archived first.
+    fn get_archived_threads(
+        &self,
+        channel_id: &str,
+        before: Option<&DateTime<Utc>>,
+        limit: Option<i32>,
+    ) -> Result<ThreadList>;
+}
+
+impl<T: Transport> GetThreads for MessageGetter<T> {
+    fn get_channel(&self, channel_id: &str) -> Result<Channel> {
+        self.get_json(format!("/channels/{channel_id}").as_str())
+    }
+
+    fn get_message(&self, channel_id: &str, message_id: &str) -> Result<Message> {
+        self.get_json(format!("/channels/{channel_id}/messages/{message_id}").as_str())
+    }
+
+    fn get_active_threads(&self, guild_id: &str) -> Result<ThreadList> {
+        self.get_json(format!("/guilds/{guild_id}/threads/active").as_str())
+    }
+
+    fn get_archived_threads(
+        &self,
+        channel_id: &str,
+        before: Option<&DateTime<Utc>>,
+        limit: Option<i32>,
+    ) -> Result<ThreadList> {
+        let mut path = format!("/channels/{channel_id}/threads/archived/public?");
+        if let Some(before) = before {
+            // `Z` instead of `+00:00`, a plus would have to be escaped in the query.
+            let before = before.to_rfc3339_opts(SecondsFormat::Millis, true);
+            path.push_str(format!("before={before}&").as_str());
+        }
+        if let Some(limit) = limit {
+            path.push_str(format!("limit={limit}").as_str());
+        }
+        self.get_json(path.trim_end_matches(['?', '&']))
+    }
+}
+
+impl<C: GetMsgs + SendMsgs + GetThreads> MessageService<C> {
+    /// Retrieves the memes of `wanted_month` from all channels of `competition`.
+    ///
+    /// With `include_threads`, messages in threads of the input channels take part as well. Forum
+    /// channels have no messages of their own, there every post's starter message is a meme.
+    ///
+    /// Threads are fetched one after another: per input channel the channel itself, the guild's
+    /// active threads and a page of archived threads per 100 archived since the period started,
+    /// then the history of every thread, one page per 100 messages, or a forum post's starter
+    /// message.
+    pub fn get_memes_for_competition(
+        &self,
+        competition: &Competition,
+        wanted_month: &DateTime<Utc>,
+    ) -> Result<Vec<Message>>
+    where
+        C: Sync,
+    {
+        if !competition.include_threads {
+            return self.get_memes_for_channels(&competition.in_channel_ids, wanted_month);
+        }
+
+        let mut result: Vec<Message> = Vec::new();
+        let mut text_channels: Vec<Channel> = Vec::new();
+        for channel_id in &competition.in_channel_ids {
+            let channel = self.client.get_channel(channel_id)?;
+            result.extend(self.get_memes_in_threads(&channel, wanted_month)?);
+            if !channel.is_forum() {
+                text_channels.push(channel);
+            }
+        }
+        let text_channel_ids: Vec<String> = text_channels.iter().map(|c| c.id.clone()).collect();
+        let mut msgs = self.get_memes_for_channels(&text_channel_ids, wanted_month)?;
+        for msg in &mut msgs {
+            let channel = text_channels.iter().find(|c| c.id == msg.channel_id);
+            msg.guild_id = channel.and_then(|channel| channel.guild_id.clone());
+        }
+        result.extend(msgs);
+        result.sort_by_key(|msg| msg.timestamp);
+        Ok(result)
+    }
+
+    /// Retrieves the memes of `wanted_month` posted in threads of `parent`.
+    ///
+    /// For forum channels only the starter message of each post counts, categorized by the
+    /// post's tags. Returned messages know their parent, see `Message::source_channel_id`, and
+    /// its guild.
+    pub fn get_memes_in_threads(
+        &self,
+        parent: &Channel,
+        wanted_month: &DateTime<Utc>,
+    ) -> Result<Vec<Message>> {
+        let period = Period::month_of(wanted_month)?;
+        let mut result: Vec<Message> = Vec::new();
+
+        for thread in self.get_threads(parent, &period)? {
+            let mut msgs = if parent.is_forum() {
+                self.get_starter_message(&thread)?
+                    .filter(|msg| period.contains(&msg.timestamp))
+                    .into_iter()
+                    .collect()
+            } else {
+                self.get_memes_for_month(thread.id.as_str(), wanted_month)?
+            };
+            for msg in &mut msgs {
+                msg.thread_parent_id = Some(parent.id.clone());
+                msg.guild_id = parent.guild_id.clone();
+                msg.categories = parent.tag_names(&thread);
+            }
+            result.extend(msgs);
+        }
+        Ok(result)
+    }
+
+    /// Finds the active and archived threads of `parent` that may hold messages of `period`.
+    fn get_threads(&self, parent: &Channel, period: &Period) -> Result<Vec<Channel>> {
+        let mut threads: Vec<Channel> = Vec::new();
+
+        match parent.guild_id.as_deref() {
+            Some(guild_id) => threads.extend(
+                self.client
+                    .get_active_threads(guild_id)?
+                    .threads
+                    .into_iter()
+                    .filter(|thread| thread.parent_id.as_deref() == Some(parent.id.as_str())),
+            ),
+            None => warn!(
+                "Channel {} has no guild, skipping active threads",
+                parent.id
+            ),
+        }
+
+        // Archived threads come most recently archived first, anything archived before the
+        // period started cannot hold messages of it.
+        let mut before: Option<DateTime<Utc>> = None;
+        loop {
+            let page = self.client.get_archived_threads(
+                parent.id.as_str(),
+                before.as_ref(),
+                Some(THREAD_PAGE_SIZE),
+            )?;
+            let last_archived = page
+                .threads
+                .iter()
+                .filter_map(|thread| thread.thread_metadata.as_ref())
+                .map(|metadata| metadata.archive_timestamp)
+                .min();
+            let has_more = page.has_more;
+            threads.extend(page.threads.into_iter().filter(|thread| {
+                thread
+                    .thread_metadata
+                    .as_ref()
+                    .is_none_or(|metadata| metadata.archive_timestamp >= period.start)
+            }));
+
+            match last_archived {
+                Some(last_archived) if has_more && last_archived >= period.start => {
+                    before = Some(last_archived)
+                }
+                _ => break,
+            }
+        }
+
+        // A thread's id is the snowflake of its creation, later threads cannot hold the memes.
+        threads.retain(|thread| {
+            thread
+                .id
+                .parse()
+                .ok()
+                .and_then(timestamp_from_snowflake)
+                .is_none_or(|created| created < period.end)
+        });
+        debug!("Found {} threads in {}", threads.len(), parent.id);
+        Ok(threads)
+    }
+
+    /// The message a forum post was opened with, `None` if it was deleted.
+    fn get_starter_message(&self, thread: &Channel) -> Result<Option<Message>> {
+        // The starter message shares its id with the thread.
+        match self
+            .client
+            .get_message(thread.id.as_str(), thread.id.as_str())
+        {
+            Ok(msg) => Ok(Some(msg)),
+            Err(Error::Discord { code, .. }) if code == UNKNOWN_MESSAGE => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+use std::io::Read;
+
+use reqwest::blocking::{multipart, Client};
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl Method {
+    /// Whether repeating the request has the same effect as sending it once.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Method::Post | Method::Patch)
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A file or field of a `multipart/form-data` body.
+#[derive(Debug, Clone)]
+pub struct Part {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Body {
+    Json(String),
+    Multipart(Vec<Part>),
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Body>,
+    /// Fail with `Error::TooLarge` instead of reading more of the response body than this
+    pub max_body_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Returns the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Sends HTTP requests on behalf of the Discord API clients.
+///
+/// `MessageGetter` only talks to Discord through a `Transport`, so middleware (retries,
+/// recording, logging) can be stacked around the reqwest implementation and tests can swap in
+/// doubles. Non-success statuses are returned as responses, not errors; interpreting them is up
+/// to the caller (see `request::read_response`).
+pub trait Transport {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+
+    fn get(&self, url: &str, headers: Vec<(String, String)>) -> Result<HttpResponse> {
+        self.send(HttpRequest {
+            method: Method::Get,
+            url: url.to_string(),
+            headers,
+            body: None,
+            max_body_bytes: None,
+        })
+    }
+
+    /// GETs `url` without reading more than `max_bytes` of the body.
+    fn get_capped(
+        &self,
+        url: &str,
+        headers: Vec<(String, String)>,
+        max_bytes: u64,
+    ) -> Result<HttpResponse> {
+        self.send(HttpRequest {
+            method: Method::Get,
+            url: url.to_string(),
+            headers,
+            body: None,
+            max_body_bytes: Some(max_bytes),
+        })
+    }
+
+    fn post(
+        &self,
+        url: &str,
+        headers: Vec<(String, String)>,
+        json: String,
+    ) -> Result<HttpResponse> {
+        self.send(HttpRequest {
+            method: Method::Post,
+            url: url.to_string(),
+            headers,
+            body: Some(Body::Json(json)),
+            max_body_bytes: None,
+        })
+    }
+
+    fn put(&self, url: &str, headers: Vec<(String, String)>) -> Result<HttpResponse> {
+        self.send(HttpRequest {
+            method: Method::Put,
+            url: url.to_string(),
+            headers,
+            body: None,
+            max_body_bytes: None,
+        })
+    }
+
+    fn patch(
+        &self,
+        url: &str,
+        headers: Vec<(String, String)>,
+        json: String,
+    ) -> Result<HttpResponse> {
+        self.send(HttpRequest {
+            method: Method::Patch,
+            url: url.to_string(),
+            headers,
+            body: Some(Body::Json(json)),
+            max_body_bytes: None,
+        })
+    }
+
+    fn delete(&self, url: &str, headers: Vec<(String, String)>) -> Result<HttpResponse> {
+        self.send(HttpRequest {
+            method: Method::Delete,
+            url: url.to_string(),
+            headers,
+            body: None,
+            max_body_bytes: None,
+        })
+    }
+
+    fn multipart(
+        &self,
+        url: &str,
+        headers: Vec<(String, String)>,
+        parts: Vec<Part>,
+    ) -> Result<HttpResponse> {
+        self.send(HttpRequest {
+            method: Method::Post,
+            url: url.to_string(),
+            headers,
+            body: Some(Body::Multipart(parts)),
+            max_body_bytes: None,
+        })
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        (**self).send(request)
+    }
+}
+
+/// The default transport, backed by `reqwest::blocking::Client`.
+#[derive(Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let method = match request.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Delete => reqwest::Method::DELETE,
+        };
+
+        let max_body_bytes = request.max_body_bytes;
+        let mut builder = self.client.request(method, request.url.as_str());
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        builder = match request.body {
+            Some(Body::Json(json)) => builder
+                .header("Content-Type", "application/json")
+                .body(json),
+            Some(Body::Multipart(parts)) => {
+                let mut form = multipart::Form::new();
+                for part in parts {
+                    let mut file =
+                        multipart::Part::bytes(part.data).mime_str(&part.content_type)?;
+                    if let Some(filename) = part.filename {
+                        file = file.file_name(filename);
+                    }
+                    form = form.part(part.name, file);
+                }
+                builder.multipart(form)
+            }
+            None => builder,
+        };
+
+        let res = self.client.execute(builder.build()?)?;
+        let too_large = |max_bytes| Error::TooLarge {
+            url: request.url.clone(),
+            max_bytes,
+        };
+        if let Some(max_bytes) = max_body_bytes {
+            if res.content_length().is_some_and(|len| len > max_bytes) {
+                return Err(too_large(max_bytes));
+            }
+        }
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                (name.to_string(), value)
+            })
+            .collect();
+        let body = match max_body_bytes {
+            // The length may be missing or wrong, so never read past the cap either way.
+            Some(max_bytes) => {
+                let mut body = Vec::new();
+                res.take(max_bytes.saturating_add(1))
+                    .read_to_end(&mut body)?;
+                if body.len() as u64 > max_bytes {
+                    return Err(too_large(max_bytes));
+                }
+                body
+            }
+            None => res.bytes()?.to_vec(),
+        };
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
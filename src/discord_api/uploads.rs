@@ -0,0 +1,88 @@
+use log::debug;
+
+use crate::discord_api::messages::MessageGetter;
+use crate::discord_api::request::read_response;
+use crate::discord_api::transport::{Part, Transport};
+use crate::models::discord::{MessageBody, PartialAttachment};
+use crate::prelude::*;
+
+/// Discord rejects messages with more files than this.
+pub const MAX_FILES: usize = 10;
+
+/// A file to upload with a message.
+#[derive(Debug, Clone)]
+pub struct FileUpload {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl FileUpload {
+    /// The url embeds of the same message show this file with.
+    pub fn attachment_url(&self) -> String {
+        format!("attachment://{}", self.filename)
+    }
+}
+
+/// Trait for downloading files, e.g. memes from Discord's CDN.
+pub trait DownloadFiles {
+    /// Downloads `url`, failing if it is larger than `max_bytes`.
+    fn download(&self, url: &str, max_bytes: u64) -> Result<Vec<u8>>;
+}
+
+impl<T: Transport> DownloadFiles for MessageGetter<T> {
+    fn download(&self, url: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        debug!("Downloading {url}");
+        // CDN and link hosts get no Discord headers, the bot token must not leak to them.
+        let res = self.transport.get_capped(url, vec![], max_bytes)?;
+        if !res.is_success() {
+            read_response(res)?;
+            return Err(Error::Generic(format!("Download of {url} failed")));
+        }
+        // Transports that cannot stream, like test doubles, buffer the whole body.
+        if res.body.len() as u64 > max_bytes {
+            return Err(Error::TooLarge {
+                url: url.to_string(),
+                max_bytes,
+            });
+        }
+        Ok(res.body)
+    }
+}
+
+/// The `multipart/form-data` parts of a message with files: the message itself as
+/// `payload_json`, followed by one `files[n]` part per file.
+pub fn multipart_parts(mut message_body: MessageBody, files: Vec<FileUpload>) -> Result<Vec<Part>> {
+    if files.len() > MAX_FILES {
+        return Err(Error::MessageLimit(format!(
+            "A message can carry at most {MAX_FILES} files, not {}",
+            files.len()
+        )));
+    }
+
+    message_body.payload_json = None;
+    message_body.attachments = Some(
+        files
+            .iter()
+            .enumerate()
+            .map(|(id, file)| PartialAttachment {
+                id,
+                filename: file.filename.clone(),
+            })
+            .collect(),
+    );
+
+    let mut parts = vec![Part {
+        name: "payload_json".to_string(),
+        filename: None,
+        content_type: "application/json".to_string(),
+        data: serde_json::to_vec(&message_body)?,
+    }];
+    parts.extend(files.into_iter().enumerate().map(|(id, file)| Part {
+        name: format!("files[{id}]"),
+        filename: Some(file.filename),
+        content_type: file.content_type,
+        data: file.data,
+    }));
+    Ok(parts)
+}
@@ -1,5 +1,6 @@
 use std::env;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     /// For starter, to remove as code matures.
@@ -10,12 +11,35 @@ pub enum Error {
     #[error("Static error: {0}")]
     Static(&'static str),
 
+    /// Discord answered with an error envelope.
+    #[error("Discord API error {status} (code {code}): {message}")]
+    Discord {
+        status: u16,
+        code: i32,
+        message: String,
+    },
+
+    /// A response body exceeded the `max_bytes` its request allowed.
+    #[error("{url} is larger than the {max_bytes} bytes allowed")]
+    TooLarge { url: String, max_bytes: u64 },
+
+    /// A message exceeds one of Discord's limits, see `message_limits`.
+    #[error("{0}")]
+    MessageLimit(String),
+
+    /// Discord answered with 429, `retry_after` is in seconds.
+    #[error("Rate limited, retry after {retry_after}s")]
+    RateLimited { retry_after: f64, global: bool },
+
     #[error(transparent)]
     ReqwestError(#[from] reqwest::Error),
-    
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
     #[error(transparent)]
-    EnvError(#[from]  env::VarError),
-    
+    EnvError(#[from] env::VarError),
+
     #[error(transparent)]
     IO(#[from] std::io::Error),
 }
@@ -1,93 +1,624 @@
 #![allow(unused)] // For beginning only.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
-use reqwest::blocking::Client;
-use serde::Serialize;
+use log::{debug, warn};
 
-use crate::discord_api::messages::{MessageGetter, MessageService};
-use crate::models::discord::{Embed, Message};
+use crate::discord_api::actions::MessageActions;
+use crate::discord_api::attachments::RefreshUrls;
+use crate::discord_api::channels::EditChannels;
+use crate::discord_api::messages::{GetMsgs, MessageGetter, MessageService, SendMsgs};
+use crate::discord_api::pins::PinMessages;
+use crate::discord_api::roles::ManageRoles;
+use crate::discord_api::threads::GetThreads;
+use crate::discord_api::uploads::{DownloadFiles, FileUpload};
+use crate::models::discord::{AllowedMentions, Embed, Message, MessageBody, MessageReference};
 use crate::prelude::*;
+use crate::utils::archive::{Archive, ArchivedWinner};
+use crate::utils::clock::{Clock, SystemClock};
+use crate::utils::competition::Competition;
 use crate::utils::config::Config;
+use crate::utils::embed_builder::EmbedBuilder;
+use crate::utils::i18n::Locale;
 use crate::utils::last_month_date::last_month_date;
+use crate::utils::media::{file_name, meme_media, Media};
 use crate::utils::message_body_builder::MessageBodyBuilder;
+use crate::utils::period::Period;
+use crate::utils::state::StateFile;
 
 mod discord_api;
 mod error;
 mod models;
 mod prelude;
+#[cfg(test)]
 mod tests;
 mod utils;
 
 fn main() -> Result<()> {
-    let last_month = last_month_date().expect("???");
+    let last_month = last_month_date(&SystemClock).expect("???");
     println!("{:#?}", &last_month);
     //
     let message_service = MessageService::new(MessageGetter::new());
+    let competition = Competition::from_config(Config::get());
+
+    announce(&message_service, &competition, &last_month, &SystemClock)
+}
 
-    let msgs: Vec<Message> = message_service.get_memes_for_month(&last_month);
+/// Collects the memes of `month` from all channels of `competition`, ranks them by the upvote
+/// emoji and posts the top three, plus the best meme of each channel if channel awards are on.
+/// The texts of the announcement are rendered from the competition's `templates`. Afterwards
+/// the winning messages get medals and replies if the competition wants them.
+///
+/// CDN links that expired according to `clock` are refreshed first. With `reupload_winners` the
+/// memes are attached to the announcement, so it outlives the expiring links; memes exceeding
+/// the upload budget are linked instead. With an `archive_dir` the winners are kept there too.
+pub fn announce<C>(
+    message_service: &MessageService<C>,
+    competition: &Competition,
+    month: &DateTime<Utc>,
+    clock: &impl Clock,
+) -> Result<()>
+where
+    C: GetMsgs
+        + SendMsgs
+        + GetThreads
+        + DownloadFiles
+        + RefreshUrls
+        + MessageActions
+        + PinMessages
+        + ManageRoles
+        + EditChannels
+        + Sync,
+{
+    let upvote_emoji = competition.upvote_emoji.as_str();
+    let msgs: Vec<Message> = message_service.get_memes_for_competition(competition, month)?;
 
-    let timestamps: Vec<DateTime<Utc>> = (&msgs).into_iter().map(|msg| msg.timestamp).collect();
+    let timestamps: Vec<DateTime<Utc>> = msgs.iter().map(|msg| msg.timestamp).collect();
     timestamps.iter().enumerate().for_each(|(idx, ts)| {
-        println!("{}, {:#?}", idx, ts);
+        debug!("{}, {:#?}", idx, ts);
     });
 
     let msgs_as_ref = msgs.iter().collect();
+    let mut msgs_with_upvote = sort_messages_by_upvote(msgs_as_ref, upvote_emoji);
+    if let Err(err) = message_service.refresh_expired_urls(&mut msgs_with_upvote, clock) {
+        warn!("Could not refresh expired links, they may not show: {err}");
+    }
 
-    let upvote_emoji: String = Config::get().upvote_emoji.clone();
-    let msgs_with_upvote = sort_messages_by_upvote(msgs_as_ref, upvote_emoji.as_ref());
+    debug!("{:#?}", &msgs_with_upvote.first());
 
-    println!("{:#?}", &msgs_with_upvote.first());
+    if msgs_with_upvote.is_empty() {
+        warn!("No upvoted memes in {}, nothing to announce", month);
+        return Ok(());
+    }
 
-    let client = Client::new();
-    let config: &Config = Config::get();
+    let locale = competition.locale;
+    let show_channel = competition.is_multi_channel();
+    let mut winners: Vec<Winner> = msgs_with_upvote
+        .iter()
+        .take(PODIUM_SIZE)
+        .enumerate()
+        .map(|(idx, msg)| {
+            let rank = HashMap::from([("rank", (idx + 1).to_string())]);
+            Ok(Winner {
+                place: locale.format("place.podium", &rank)?,
+                podium: Some(idx),
+                msg,
+                show_channel,
+            })
+        })
+        .collect::<Result<_>>()?;
 
-    let msgs_size = msgs_with_upvote.len();
-    let mut msg_body_builder = MessageBodyBuilder::new("This month top three memes were:");
+    if competition.channel_awards && show_channel {
+        for channel_id in &competition.in_channel_ids {
+            // Channels whose best meme made the podium are honored there already.
+            let channel_winner = msgs_with_upvote
+                .iter()
+                .position(|msg| msg.source_channel_id() == channel_id)
+                .filter(|idx| *idx >= PODIUM_SIZE)
+                .map(|idx| &msgs_with_upvote[idx]);
+            if let Some(msg) = channel_winner {
+                winners.push(Winner {
+                    place: locale.text("place.best_in_channel"),
+                    podium: None,
+                    msg,
+                    show_channel: true,
+                });
+            }
+        }
+    }
 
-    // todo: what to do if there are no winners
-    let first_msg = msgs_with_upvote.first().unwrap();
-    msg_body_builder = msg_body_builder.add_embed(create_winner_embed("1.", first_msg));
+    let archive = competition.archive_dir.as_ref().map(|dir| {
+        Archive::new(
+            dir,
+            competition.name.as_str(),
+            competition.archive_max_bytes,
+        )
+    });
+    let mut guild_ids: HashMap<String, Option<String>> = HashMap::new();
+    let mut upload_budget = competition.upload_max_bytes;
+    let period = Period::month_of(month)?;
+    let intro_vars = HashMap::from([
+        ("competition", competition.name.clone()),
+        ("period", period.name(locale)?),
+        ("count", winners.len().to_string()),
+    ]);
+    let intro = competition.templates.intro.render(&intro_vars)?;
+    let mut msg_body_builder = MessageBodyBuilder::new(intro.as_str());
+    let mentions = mentions_line(competition, &winners);
+    if !mentions.is_empty() {
+        msg_body_builder = msg_body_builder.add_line(mentions.as_str());
+    }
+    if let Some(allowed_mentions) = allowed_mentions(competition, &winners) {
+        msg_body_builder = msg_body_builder.allowed_mentions(allowed_mentions);
+    }
+    let mut to_archive: Vec<ArchivedWinner> = Vec::new();
+    for (idx, winner) in winners.iter().enumerate() {
+        let msg = winner.msg;
+        let media = meme_media(msg).expect("Only memes with media are ranked");
+        let guild_id = guild_ids
+            .entry(msg.source_channel_id().to_string())
+            .or_insert_with(|| {
+                // Looking up the input channel is only needed if fetching skipped it.
+                msg.guild_id
+                    .clone()
+                    .or_else(|| guild_of(&message_service.client, msg.source_channel_id()))
+            });
+        let link = message_link(guild_id.as_deref(), msg);
+        let upload = if competition.reupload_winners {
+            download_meme(
+                &message_service.client,
+                msg,
+                &media,
+                idx,
+                &mut upload_budget,
+            )
+        } else {
+            None
+        };
+        if archive.is_some() {
+            to_archive.push(ArchivedWinner {
+                rank: idx + 1,
+                place: winner.place.as_str(),
+                msg,
+                media: media.clone(),
+                score: upvote_count(msg, upvote_emoji),
+                message_link: link.clone(),
+                upload: upload.clone(),
+            });
+        }
+        let vars = winner_vars(&intro_vars, winner, upvote_emoji, link.as_str());
+        msg_body_builder = add_winner(msg_body_builder, &media, upload, |media| {
+            create_winner_embed(winner, media, competition, link.as_str(), &vars)
+        })?;
+    }
 
-    if msgs_size >= 2 {
-        let second_msg = msgs_with_upvote.get(1).unwrap();
+    let mut sent = Vec::new();
+    for (body, files) in msg_body_builder.build_messages(competition.overflow)? {
+        sent.push(message_service.send_message_with_files(body, files)?);
+    }
+    // Only what was announced is archived.
+    if let Some(archive) = &archive {
+        for archived in &to_archive {
+            if let Err(err) = archive.store_winner(&message_service.client, month, archived) {
+                warn!("Could not archive {}: {err}", archived.msg.id);
+            }
+        }
+    }
+    if competition.crosspost {
+        if let Err(err) = message_service.publish(&sent) {
+            warn!("Could not publish the announcement: {err}");
+        }
+    }
+    celebrate_winners(&message_service.client, competition, &winners, &intro_vars);
+    if competition.pin_winners || competition.winner_role_id.is_some() {
+        if let Err(err) = honor_winner(message_service, competition, &winners, &guild_ids, clock) {
+            warn!("Could not update the state of the winner: {err}");
+        }
+    }
+    if let Some(channel_id) = &competition.topic_channel_id {
+        let topic = champion_topic(competition, &winners, &intro_vars).and_then(|topic| {
+            topic.map_or(Ok(false), |topic| {
+                message_service.update_topic(channel_id, topic.as_str())
+            })
+        });
+        if let Err(err) = topic {
+            warn!("Could not update the topic of {channel_id}: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// A meme placed in the announcement.
+struct Winner<'a> {
+    /// `1.`, `2.`, `3.` or the name of a special award
+    place: String,
+    /// 0 to 2 for the top three, `None` for special awards
+    podium: Option<usize>,
+    msg: &'a Message,
+    /// Whether to name the channel the meme was posted in
+    show_channel: bool,
+}
+
+/// How many of the best memes make the podium.
+const PODIUM_SIZE: usize = 3;
+
+/// Gold, silver and bronze.
+const PODIUM_COLORS: [u32; PODIUM_SIZE] = [0xFFD700, 0xC0C0C0, 0xCD7F32];
 
-        msg_body_builder = msg_body_builder.add_embed(create_winner_embed("2.", second_msg));
+/// Reactions for the top three.
+const MEDALS: [&str; PODIUM_SIZE] = ["🥇", "🥈", "🥉"];
+
+/// Reaction for special awards.
+const AWARD_MEDAL: &str = "🏅";
+
+/// Marks the winning messages where they were posted, so people browsing the meme channels see
+/// them: reacts with their medal and replies with their place, as `competition` wants.
+///
+/// The announcement is out already, so failures are only logged.
+fn celebrate_winners(
+    client: &impl MessageActions,
+    competition: &Competition,
+    winners: &[Winner],
+    intro_vars: &HashMap<&str, String>,
+) {
+    for winner in winners {
+        let msg = winner.msg;
+        if competition.react_to_winners {
+            let medal = winner
+                .podium
+                .and_then(|podium| MEDALS.get(podium))
+                .unwrap_or(&AWARD_MEDAL);
+            if let Err(err) = client.add_reaction(msg.channel_id.as_str(), msg.id.as_str(), medal) {
+                warn!("Could not react to winner {}: {err}", msg.id);
+            }
+        }
+        if competition.reply_to_winners {
+            let reply = winner_reply(competition, winner, intro_vars)
+                .and_then(|reply| client.send_message_to(msg.channel_id.as_str(), reply));
+            if let Err(err) = reply {
+                warn!("Could not reply to winner {}: {err}", msg.id);
+            }
+        }
     }
-    if msgs_size >= 3 {
-        let third_msg = msgs_with_upvote.get(2).unwrap();
+}
+
+/// Pins the first place of `winners` and passes the winner role on to its author, as
+/// `competition` wants. What was pinned and who holds the role is kept in the state file, so
+/// pins can be rotated out and the role taken back later.
+fn honor_winner<C: GetMsgs + SendMsgs + PinMessages + ManageRoles>(
+    message_service: &MessageService<C>,
+    competition: &Competition,
+    winners: &[Winner],
+    guild_ids: &HashMap<String, Option<String>>,
+    clock: &impl Clock,
+) -> Result<()> {
+    let Some(winner) = winners.iter().find(|winner| winner.podium == Some(0)) else {
+        return Ok(());
+    };
+    let msg = winner.msg;
+    let state_file = StateFile::new(competition.state_file.as_str());
+    let mut state = state_file.load()?;
 
-        msg_body_builder = msg_body_builder.add_embed(create_winner_embed("3.", third_msg));
+    if competition.pin_winners {
+        let pinned = message_service.pin_winner(
+            competition.name.as_str(),
+            msg,
+            competition.pin_limit,
+            &mut state.pinned_winners,
+            clock.now(),
+        );
+        if let Err(err) = pinned {
+            warn!("Could not pin the winner {}: {err}", msg.id);
+        }
     }
+    if let Some(role_id) = &competition.winner_role_id {
+        let guild_id = guild_ids.get(msg.source_channel_id()).cloned().flatten();
+        let rotated = guild_id
+            .ok_or_else(|| Error::Generic(format!("{} has no guild", msg.source_channel_id())))
+            .and_then(|guild_id| {
+                message_service.rotate_winner_role(
+                    competition.name.as_str(),
+                    guild_id.as_str(),
+                    role_id.as_str(),
+                    msg.author.id.as_str(),
+                    &mut state,
+                    clock.now(),
+                )
+            });
+        if let Err(err) = rotated {
+            warn!("Could not pass on the winner role: {err}");
+        }
+    }
+    state_file.save(&state)
+}
 
-    message_service.send_message(msg_body_builder.build());
+/// The channel topic naming the author of the first place of `winners`, if there is one.
+fn champion_topic(
+    competition: &Competition,
+    winners: &[Winner],
+    intro_vars: &HashMap<&str, String>,
+) -> Result<Option<String>> {
+    let Some(winner) = winners.iter().find(|winner| winner.podium == Some(0)) else {
+        return Ok(None);
+    };
+    let mut vars = intro_vars.clone();
+    vars.extend([
+        ("place", winner.place.clone()),
+        ("author_name", winner.msg.author.display_name().to_string()),
+    ]);
+    competition.templates.topic.render(&vars).map(Some)
+}
 
-    Ok(())
+/// The reply telling the author of `winner` about their place.
+fn winner_reply(
+    competition: &Competition,
+    winner: &Winner,
+    intro_vars: &HashMap<&str, String>,
+) -> Result<MessageBody> {
+    let mut vars = intro_vars.clone();
+    vars.insert("place", winner.place.clone());
+    let text = competition.locale.format("reply.winner", &vars)?;
+    Ok(MessageBodyBuilder::new(text.as_str())
+        .reply_to(MessageReference {
+            message_id: winner.msg.id.clone(),
+            channel_id: Some(winner.msg.channel_id.clone()),
+            fail_if_not_exists: Some(false),
+        })
+        .allowed_mentions(AllowedMentions {
+            replied_user: competition.ping_winners,
+            ..Default::default()
+        })
+        .build())
 }
 
-fn create_winner_embed(place: &str, first_msg: &Message) -> Embed {
-    let upvote_emoji: String = Config::get().upvote_emoji.clone();
-    let user_id = first_msg.author.id.as_str();
-    let upvotes: i32 = first_msg
-        .clone()
-        .reactions
-        .unwrap()
-        .clone()
+/// The ids of the winners' authors, each once.
+fn winner_ids(winners: &[Winner]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for winner in winners {
+        if !ids.contains(&winner.msg.author.id) {
+            ids.push(winner.msg.author.id.clone());
+        }
+    }
+    ids
+}
+
+/// The mentions notifying the role and winners of `competition`, mentions in embeds notify
+/// no one.
+fn mentions_line(competition: &Competition, winners: &[Winner]) -> String {
+    let role = competition
+        .notify_role_id
+        .iter()
+        .map(|role_id| format!("<@&{role_id}>"));
+    let users = winner_ids(winners)
         .into_iter()
-        .find(|rec| rec.emoji.name == upvote_emoji)
-        .unwrap()
-        .count;
+        .filter(|_| competition.ping_winners)
+        .map(|user_id| format!("<@{user_id}>"));
+    role.chain(users).collect::<Vec<_>>().join(" ")
+}
+
+/// With `suppress_mentions` only the mentions of `mentions_line` notify anyone, even if a
+/// template mentions `@everyone` or other roles.
+fn allowed_mentions(competition: &Competition, winners: &[Winner]) -> Option<AllowedMentions> {
+    if !competition.suppress_mentions {
+        return None;
+    }
+    Some(AllowedMentions {
+        parse: Vec::new(),
+        users: if competition.ping_winners {
+            winner_ids(winners)
+        } else {
+            Vec::new()
+        },
+        roles: competition.notify_role_id.iter().cloned().collect(),
+        replied_user: false,
+    })
+}
+
+/// Adds the embed `winner_embed` makes for the winner's `media`, with the meme attached if it
+/// was downloaded as `upload`.
+///
+/// Media an embed cannot show, like videos, is linked in the content unless attached, so
+/// Discord shows a player or file preview below the announcement either way.
+fn add_winner(
+    builder: MessageBodyBuilder,
+    media: &Media,
+    upload: Option<FileUpload>,
+    winner_embed: impl Fn(&Media) -> Result<Embed>,
+) -> Result<MessageBodyBuilder> {
+    let Some(upload) = upload else {
+        let builder = builder.add_embed(winner_embed(media)?);
+        return Ok(if media.is_embeddable() {
+            builder
+        } else {
+            builder.add_line(media.url.as_str())
+        });
+    };
 
-    Embed::new(
-        place,
-        format!("MeMe von <@{}>\nMit {} upvotes", user_id, upvotes).as_str(),
-        first_msg.attachments.first().unwrap().url.as_str(),
+    let embed = if media.is_embeddable() {
+        let uploaded = Media {
+            url: upload.attachment_url(),
+            preview_url: None,
+            ..media.clone()
+        };
+        winner_embed(&uploaded)?
+    } else {
+        winner_embed(media)?
+    };
+    Ok(builder.add_embed(embed).add_file(upload))
+}
+
+/// Downloads the meme of `msg` to attach it as the `idx`th file.
+///
+/// Returns `None`, leaving the meme linked, if it does not fit into `budget` or cannot be
+/// downloaded. Otherwise its size is taken from `budget`.
+fn download_meme(
+    downloader: &impl DownloadFiles,
+    msg: &Message,
+    media: &Media,
+    idx: usize,
+    budget: &mut u64,
+) -> Option<FileUpload> {
+    let known_size = msg
+        .attachments
+        .iter()
+        .find(|attachment| attachment.url == media.url)
+        .map(|attachment| attachment.size.max(0) as u64);
+    if known_size.is_some_and(|size| size > *budget) {
+        warn!("{} is too large to upload, linking it instead", media.url);
+        return None;
+    }
+
+    let data = match downloader.download(media.url.as_str(), *budget) {
+        Ok(data) => data,
+        Err(err) => {
+            warn!(
+                "Could not download {}, linking it instead: {err}",
+                media.url
+            );
+            return None;
+        }
+    };
+    *budget -= data.len() as u64;
+
+    let filename = msg
+        .attachments
+        .iter()
+        .find(|attachment| attachment.url == media.url)
+        .map(|attachment| file_name(attachment.filename.as_str()))
+        .unwrap_or_else(|| file_name(media.url.as_str()));
+    Some(FileUpload {
+        filename: format!("{idx}_{filename}"),
+        content_type: media
+            .content_type
+            .clone()
+            .unwrap_or_else(|| "application/octet-stream".to_string()),
+        data,
+    })
+}
+
+/// The guild `channel_id` belongs to, `None` if it cannot be looked up.
+fn guild_of(client: &impl GetThreads, channel_id: &str) -> Option<String> {
+    match client.get_channel(channel_id) {
+        Ok(channel) => channel.guild_id,
+        Err(err) => {
+            warn!("Could not look up the guild of {channel_id}: {err}");
+            None
+        }
+    }
+}
+
+/// The jump link to `msg`, Discord resolves `@me` for messages outside of guilds.
+fn message_link(guild_id: Option<&str>, msg: &Message) -> String {
+    format!(
+        "https://discord.com/channels/{}/{}/{}",
+        guild_id.unwrap_or("@me"),
+        msg.channel_id,
+        msg.id
     )
 }
 
+/// How often `msg` was upvoted with `upvote_emoji`.
+fn upvote_count(msg: &Message, upvote_emoji: &str) -> i32 {
+    msg.reactions
+        .iter()
+        .flatten()
+        .find(|rec| rec.emoji.name == upvote_emoji)
+        .map_or(0, |rec| rec.count)
+}
+
+/// The variables of the embed templates of `winner`, on top of those of the intro.
+fn winner_vars<'a>(
+    intro_vars: &HashMap<&'a str, String>,
+    winner: &Winner,
+    upvote_emoji: &str,
+    message_link: &str,
+) -> HashMap<&'a str, String> {
+    let msg = winner.msg;
+    let mut vars = intro_vars.clone();
+    vars.extend([
+        ("place", winner.place.clone()),
+        ("author", format!("<@{}>", msg.author.id)),
+        ("author_name", msg.author.display_name().to_string()),
+        ("score", upvote_count(msg, upvote_emoji).to_string()),
+        ("channel", format!("<#{}>", msg.source_channel_id())),
+        ("link", message_link.to_string()),
+    ]);
+    vars
+}
+
+/// The embed of `winner`, its texts rendered from the templates of `competition`: colored by
+/// its podium place, showing its author and linking to the original message.
+fn create_winner_embed(
+    winner: &Winner,
+    media: &Media,
+    competition: &Competition,
+    message_link: &str,
+    vars: &HashMap<&str, String>,
+) -> Result<Embed> {
+    let msg = winner.msg;
+    let author = &msg.author;
+    let templates = &competition.templates;
+    let locale = competition.locale;
+
+    let embed = Embed::with_media(
+        templates.title.render(vars)?.as_str(),
+        templates.description.render(vars)?.as_str(),
+        media,
+    );
+    let mut builder = EmbedBuilder::from(embed)
+        .url(message_link)
+        .timestamp(msg.timestamp)
+        .author(
+            author.display_name(),
+            None,
+            Some(author.avatar_url().as_str()),
+        );
+    if let Some(color) = winner.podium.and_then(|podium| PODIUM_COLORS.get(podium)) {
+        builder = builder.color(*color);
+    }
+    if !templates.footer.is_empty() {
+        builder = builder.footer(templates.footer.render(vars)?.as_str(), None);
+    }
+    if winner.show_channel {
+        builder = builder.field(
+            locale.text("field.channel").as_str(),
+            vars["channel"].as_str(),
+            true,
+        );
+    }
+    let score = score_breakdown(msg, competition.upvote_emoji.as_str(), locale)?;
+    Ok(builder
+        .field(locale.text("field.score").as_str(), score.as_str(), true)
+        .build())
+}
+
+/// The upvotes of `msg`, split into normal and super reactions if there are any of the latter.
+fn score_breakdown(msg: &Message, upvote_emoji: &str, locale: Locale) -> Result<String> {
+    let Some(reaction) = msg
+        .reactions
+        .iter()
+        .flatten()
+        .find(|rec| rec.emoji.name == upvote_emoji)
+    else {
+        return Ok("0".to_string());
+    };
+    let details = &reaction.count_details;
+    if details.burst == 0 {
+        return Ok(reaction.count.to_string());
+    }
+    let vars = HashMap::from([
+        ("count", reaction.count.to_string()),
+        ("normal", details.normal.to_string()),
+        ("burst", details.burst.to_string()),
+    ]);
+    locale.format("score.breakdown", &vars)
+}
+
 pub fn sort_messages_by_upvote(msgs: Vec<&Message>, upvote_emoji: &str) -> Vec<Message> {
     /// Sorts a vector of messages by the number of upvotes.
     ///
-    /// This function takes a vector of `Message` references and filters out the messages that have attachments and at least one reaction with the "👍" emoji. It then sorts the remaining messages based on the count of the "👍" reactions in ascending order.
+    /// This function takes a vector of `Message` references and filters out the messages that have an image or video (see `meme_media`) and at least one reaction with the "👍" emoji. It then sorts the remaining messages based on the count of the "👍" reactions in ascending order.
     ///
     /// # Arguments
     ///
@@ -99,17 +630,11 @@ pub fn sort_messages_by_upvote(msgs: Vec<&Message>, upvote_emoji: &str) -> Vec<M
     /// A new vector containing the sorted `Messages`
     ///
     ///
-    let mut x: Vec<&Message> = msgs
-        .clone()
-        .into_iter()
-        .filter(|msg| !msg.attachments.is_empty())
-        .collect();
-
     let mut msgs_with_upvote: Vec<&Message> = msgs
         .into_iter()
-        .filter(|msg| !msg.attachments.is_empty())
+        .filter(|msg| meme_media(msg).is_some())
         .filter(|msg| {
-            msg.reactions.as_ref().map_or(false, |reactions| {
+            msg.reactions.as_ref().is_some_and(|reactions| {
                 reactions
                     .iter()
                     .any(|rec| rec.count > 0 && rec.emoji.name == upvote_emoji)
@@ -122,7 +647,7 @@ pub fn sort_messages_by_upvote(msgs: Vec<&Message>, upvote_emoji: &str) -> Vec<M
             .reactions
             .as_ref()
             .unwrap()
-            .into_iter()
+            .iter()
             .find(|rec| rec.emoji.name == upvote_emoji)
             .unwrap()
             .count);
@@ -130,17 +655,13 @@ pub fn sort_messages_by_upvote(msgs: Vec<&Message>, upvote_emoji: &str) -> Vec<M
             .reactions
             .as_ref()
             .unwrap()
-            .into_iter()
+            .iter()
             .find(|rec| rec.emoji.name == upvote_emoji)
             .unwrap()
             .count);
 
-        msg1_count.cmp(&msg2_count)
+        msg1_count.cmp(msg2_count)
     });
 
-    msgs_with_upvote
-        .into_iter()
-        .map(|msg| msg.clone())
-        .rev()
-        .collect()
+    msgs_with_upvote.into_iter().cloned().rev().collect()
 }
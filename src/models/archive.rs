@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// The JSON sidecar stored next to every archived meme file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchiveEntry {
+    pub competition: String,
+    /// `YYYY-MM` of the competition period
+    pub month: String,
+    /// `1.`, `2.`, `3.` or the name of a special award
+    pub place: String,
+    pub author: ArchivedAuthor,
+    /// Upvotes the meme received
+    pub score: i32,
+    /// Jump link to the original message
+    pub message_link: String,
+    /// The file name the meme was posted with
+    pub original_filename: String,
+    /// The file name in the archive directory
+    pub file: String,
+    /// Hex encoded SHA-256 of the file
+    pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchivedAuthor {
+    pub id: String,
+    pub username: String,
+    pub global_name: Option<String>,
+}
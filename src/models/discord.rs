@@ -3,13 +3,15 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Channel {
-    id: String,
+    pub(crate) id: String,
     #[serde(rename = "type")]
-    _type: i32,
-    guild_id: Option<String>,
+    pub(crate) _type: i32,
+    pub(crate) guild_id: Option<String>,
     position: Option<i32>,
-    name: Option<String>,
-    topic: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) topic: Option<String>,
+    /// Missing on threads
+    #[serde(default)]
     nsfw: bool,
     last_message_id: Option<String>,
     bitrate: Option<i32>,
@@ -18,7 +20,8 @@ pub struct Channel {
     icon: Option<String>,
     owner_id: Option<String>,
     managed: Option<bool>,
-    parent_id: Option<String>,
+    /// The text or forum channel a thread belongs to
+    pub(crate) parent_id: Option<String>,
     last_pin_timestamp: Option<String>,
     rtc_region: Option<String>,
     video_quality_mode: Option<i32>,
@@ -28,25 +31,90 @@ pub struct Channel {
     permissions: Option<String>,
     flags: Option<i32>,
     total_message_sent: Option<i32>,
-    applied_tags: Option<Vec<String>>,
+    /// Only set on threads
+    pub(crate) thread_metadata: Option<ThreadMetadata>,
+    /// Tags a forum channel offers its posts
+    pub(crate) available_tags: Option<Vec<ForumTag>>,
+    /// IDs of the forum tags applied to a forum post
+    pub(crate) applied_tags: Option<Vec<String>>,
     default_thread_rate_limit_per_user: Option<i32>,
     default_sort_order: Option<i32>,
     default_forum_layout: Option<i32>,
 }
 
+impl Channel {
+    pub const GUILD_ANNOUNCEMENT: i32 = 5;
+    pub const GUILD_FORUM: i32 = 15;
+    pub const GUILD_MEDIA: i32 = 16;
+
+    /// Whether the channel only holds posts, i.e. threads, and no messages of its own.
+    pub fn is_forum(&self) -> bool {
+        matches!(self._type, Self::GUILD_FORUM | Self::GUILD_MEDIA)
+    }
+
+    /// Whether other servers can follow the channel, receiving what is crossposted there.
+    pub fn is_announcement(&self) -> bool {
+        self._type == Self::GUILD_ANNOUNCEMENT
+    }
+
+    /// Names of the forum tags `thread` carries, looked up in this forum's `available_tags`.
+    pub fn tag_names(&self, thread: &Channel) -> Vec<String> {
+        let available = self.available_tags.as_deref().unwrap_or_default();
+        thread
+            .applied_tags
+            .iter()
+            .flatten()
+            .filter_map(|tag_id| available.iter().find(|tag| tag.id == *tag_id))
+            .map(|tag| tag.name.clone())
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadMetadata {
+    pub archived: bool,
+    auto_archive_duration: i32,
+    /// When the archived status last changed
+    pub archive_timestamp: DateTime<Utc>,
+    locked: bool,
+    invitable: Option<bool>,
+    create_timestamp: Option<DateTime<Utc>>,
+}
+
+/// A tag that can be applied to posts of a forum channel
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForumTag {
+    pub id: String,
+    pub name: String,
+    moderated: bool,
+    emoji_id: Option<String>,
+    emoji_name: Option<String>,
+}
+
+/// The answer of the active and archived thread endpoints
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadList {
+    pub threads: Vec<Channel>,
+    /// Whether more archived threads are available, always `false` for active threads
+    #[serde(default)]
+    pub has_more: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Message {
     pub(crate) id: String,
     #[serde(rename = "type")]
     _type: i32,
     content: String,
-    channel_id: String,
+    pub(crate) channel_id: String,
     pub(crate) author: User,
     pub attachments: Vec<Attachment>,
-    // pub embeds: Vec<String>,
+    /// Embeds of links in the message, or of the bot that sent it
+    #[serde(default)]
+    pub embeds: Vec<Embed>,
     mentions: Vec<User>,
     mention_roles: Vec<String>,
-    pinned: bool,
+    pub(crate) pinned: bool,
     mention_everyone: bool,
     tts: bool,
     pub timestamp: DateTime<Utc>,
@@ -56,6 +124,24 @@ pub struct Message {
     interaction: Option<Interaction>,
     webhook_id: Option<String>,
     pub reactions: Option<Vec<Reaction>>,
+    /// The channel the thread this message was posted in belongs to, not sent by Discord
+    #[serde(skip)]
+    pub(crate) thread_parent_id: Option<String>,
+    /// Names of the forum tags of the post this message starts, not sent by Discord
+    #[serde(skip)]
+    pub categories: Vec<String>,
+    /// The guild of the input channel, not sent by Discord, known if the channel was looked up
+    #[serde(skip)]
+    pub(crate) guild_id: Option<String>,
+}
+
+impl Message {
+    /// The input channel this message was submitted to, the parent channel for thread messages.
+    pub fn source_channel_id(&self) -> &str {
+        self.thread_parent_id
+            .as_deref()
+            .unwrap_or(self.channel_id.as_str())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -77,22 +163,47 @@ pub struct User {
     flags: i32,
     banner: Option<String>,
     accent_color: Option<String>,
-    global_name: Option<String>,
+    pub(crate) global_name: Option<String>,
     avatar_decoration_data: Option<String>,
     banner_color: Option<String>,
     bot: Option<bool>,
 }
 
+impl User {
+    /// The name shown in the member list, the username if no display name is set.
+    pub fn display_name(&self) -> &str {
+        self.global_name
+            .as_deref()
+            .unwrap_or(self.username.as_str())
+    }
+
+    /// The CDN url of the user's avatar, the default avatar if none was uploaded.
+    pub fn avatar_url(&self) -> String {
+        match &self.avatar {
+            Some(hash) if hash.starts_with("a_") => {
+                format!("https://cdn.discordapp.com/avatars/{}/{hash}.gif", self.id)
+            }
+            Some(hash) => format!("https://cdn.discordapp.com/avatars/{}/{hash}.png", self.id),
+            None => {
+                let index = self.id.parse::<u64>().map_or(0, |id| (id >> 22) % 6);
+                format!("https://cdn.discordapp.com/embed/avatars/{index}.png")
+            }
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Attachment {
     id: String,
-    filename: String,
-    size: i32,
+    pub(crate) filename: String,
+    /// Size in bytes
+    pub(crate) size: i32,
     pub(crate) url: String,
-    proxy_url: String,
-    width: Option<i32>,
-    height: Option<i32>,
-    content_type: String,
+    pub(crate) proxy_url: String,
+    pub(crate) width: Option<i32>,
+    pub(crate) height: Option<i32>,
+    /// MIME type, missing for some old uploads
+    pub(crate) content_type: Option<String>,
     placeholder: Option<String>,
     placeholder_version: Option<i32>,
 }
@@ -100,7 +211,7 @@ pub struct Attachment {
 pub struct Reaction {
     pub emoji: Emoji,
     pub count: i32,
-    count_details: CountDetails,
+    pub(crate) count_details: CountDetails,
     burst_colors: Vec<String>,
     me_burst: bool,
     burst_me: bool,
@@ -116,9 +227,59 @@ pub struct Emoji {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CountDetails {
-    burst: i32,
-    normal: i32,
+    /// Super reactions
+    pub(crate) burst: i32,
+    pub(crate) normal: i32,
+}
+/// The error envelope Discord answers with when a request fails
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    /// Discord's JSON error code, not the HTTP status
+    pub code: i32,
+    pub message: String,
+}
+
+/// The answer of `POST /channels/{channel_id}/messages`, as far as the bot needs it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentMessage {
+    pub id: String,
+    pub channel_id: String,
+}
+
+/// The body of `PATCH /channels/{channel_id}`, fields left out stay as they are
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModifyChannelRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
 }
+
+/// The body of `POST /attachments/refresh-urls`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshUrlsRequest {
+    pub attachment_urls: Vec<String>,
+}
+
+/// The answer of `POST /attachments/refresh-urls`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshUrlsResponse {
+    pub refreshed_urls: Vec<RefreshedUrl>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshedUrl {
+    pub original: String,
+    pub refreshed: String,
+}
+
+/// The body of a 429 response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RateLimitResponse {
+    pub message: String,
+    /// Seconds to wait before retrying
+    pub retry_after: f64,
+    pub global: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MessageBody {
     pub content: String,
@@ -128,9 +289,53 @@ pub struct MessageBody {
     pub sticker_ids: Option<Vec<String>>, // Assuming snowflakes are represented as Strings
     pub payload_json: Option<String>,
     pub flags: Option<i32>,
+    /// Files uploaded along with a multipart message, referenced by their index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<PartialAttachment>>,
+    /// Who may be pinged by mentions in the content, everyone mentioned if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mentions: Option<AllowedMentions>,
+    /// The message this one replies to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_reference: Option<MessageReference>,
+}
+
+/// Points at the message a reply answers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MessageReference {
+    pub message_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    /// Send the reply as normal message if the referenced one was deleted, if `false`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_if_not_exists: Option<bool>,
+}
+
+/// Restricts the mentions in a message's content that notify someone
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AllowedMentions {
+    /// Kinds of mentions that always notify: `users`, `roles` and `everyone`
+    pub parse: Vec<String>,
+    /// Users that are notified, must be empty if `parse` contains `users`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<String>,
+    /// Roles that are notified, must be empty if `parse` contains `roles`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Whether the author of the message replied to is notified
+    #[serde(default)]
+    pub replied_user: bool,
+}
+
+/// Describes a file uploaded with a message, embeds show it via `attachment://<filename>`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartialAttachment {
+    /// Index of the `files[n]` part holding the file
+    pub id: usize,
+    pub filename: String,
 }
 /// Describes a field that can be used inside a message embed
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct EmbedField {
     /// Field title
     #[serde(rename = "name")]
@@ -138,50 +343,106 @@ pub struct EmbedField {
     /// Field value
     pub value: String,
     /// If true, the field will be displayed on the same line as the last
+    #[serde(default)]
     pub inline: bool,
 }
 
 /// Describes an embed author
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct EmbedAuthor {
     /// Author name
     pub name: String,
-    /// String of the author
-    pub String: Option<String>,
-    /// Avatar String for the author
-    pub icon_String: Option<String>,
+    /// The url the author name links to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Avatar url of the author
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// `icon_url` proxied by Discord, set on received embeds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_icon_url: Option<String>,
 }
 
 /// Describes an embed thumbnail
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EmbedThumbnail {
-    /// Thumbnail String
-    pub String: String,
+    /// Thumbnail url
+    pub url: String,
+    /// `url` proxied by Discord, set on received embeds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
 }
 
 /// Describes an embed image
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EmbedImage {
-    /// Image String
+    /// Image url
     pub url: String,
+    /// `url` proxied by Discord, set on received embeds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+}
+
+/// Describes the video of a link embed, bots cannot send these
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmbedVideo {
+    /// Video url, missing for some providers that only allow playback on their site
+    pub url: Option<String>,
+    /// `url` proxied by Discord
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+/// Describes the site a link embed was generated for, bots cannot send these
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct EmbedProvider {
+    pub name: Option<String>,
+    pub url: Option<String>,
 }
 
 /// Describes an embed footer
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct EmbedFooter {
     /// Footer text
     pub text: String,
-    /// Footer icon String
-    pub icon_String: Option<String>,
+    /// Footer icon url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// `icon_url` proxied by Discord, set on received embeds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_icon_url: Option<String>,
 }
 
 /// Describes an embed
-#[derive(Debug, Serialize, Deserialize, Default)]
+///
+/// Embeds Discord generated for links in received messages lack most fields, so everything
+/// missing falls back to its default.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
 pub struct Embed {
+    /// `rich` for bot embeds, `image`, `video`, `gifv`, `article` or `link` for link embeds
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub _type: Option<String>,
     /// The title of the embed
     pub title: String,
     /// The description of the embed
     pub description: String,
+    /// The url the title links to, for link embeds the link itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Shown in the footer, in the reader's time zone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
     /// The color of the embed
     pub color: Option<u32>,
     /// The embed author
@@ -192,6 +453,12 @@ pub struct Embed {
     pub thumbnail: Option<EmbedThumbnail>,
     /// The image of the embed
     pub image: Option<EmbedImage>,
+    /// The video of a link embed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video: Option<EmbedVideo>,
+    /// The site of a link embed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<EmbedProvider>,
     /// The footer of the embed
     pub footer: Option<EmbedFooter>,
 }
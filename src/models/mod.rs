@@ -1 +1,3 @@
+pub mod archive;
 pub mod discord;
+pub mod state;
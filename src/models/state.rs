@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What the bot remembers between runs, see `StateFile`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BotState {
+    /// Winners the bot pinned and has not unpinned yet, oldest first
+    #[serde(default)]
+    pub pinned_winners: Vec<PinnedWinner>,
+    /// Who holds the winner role of each competition
+    #[serde(default)]
+    pub role_holders: Vec<RoleHolder>,
+    /// Every role the bot granted or revoked, oldest first
+    #[serde(default)]
+    pub audit_log: Vec<AuditEntry>,
+}
+
+/// A winning message the bot pinned
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PinnedWinner {
+    pub competition: String,
+    pub channel_id: String,
+    pub message_id: String,
+    pub pinned_at: DateTime<Utc>,
+}
+
+/// The member currently holding a competition's winner role
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RoleHolder {
+    pub competition: String,
+    pub guild_id: String,
+    pub role_id: String,
+    pub user_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleAction {
+    Granted,
+    Revoked,
+}
+
+/// A role change the bot made
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub competition: String,
+    pub action: RoleAction,
+    pub guild_id: String,
+    pub role_id: String,
+    pub user_id: String,
+}
@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::fs;
+
+use crate::discord_api::messages::{GetMsgs, SendMsgs};
+use crate::discord_api::transport::{HttpRequest, HttpResponse, Transport};
+use crate::discord_api::uploads::FileUpload;
+use crate::models::discord::{Message, MessageBody, SentMessage};
+use crate::prelude::*;
+
+pub struct FakeMessageGetter;
+
+impl GetMsgs for FakeMessageGetter {
+    fn get_messages(
+        &self,
+        _channel_id: &str,
+        after: Option<String>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Message>> {
+        let path = "src/tests/data/msgs.json";
+        let msgs: Vec<Message> = serde_json::from_str(fs::read_to_string(path)?.as_str())
+            .expect("Could not parse Message");
+
+        // Mirror Discord: the `limit` messages right after `after`, newest first.
+        let after: u64 = after.map_or(0, |id| id.parse().expect("Snowflakes are numeric"));
+        let newer: Vec<Message> = msgs
+            .into_iter()
+            .filter(|msg| msg.id.parse::<u64>().expect("Snowflakes are numeric") > after)
+            .collect();
+        let skip = limit.map_or(0, |limit| newer.len().saturating_sub(limit as usize));
+        Ok(newer.into_iter().skip(skip).collect())
+    }
+
+    fn get_messages_before(
+        &self,
+        channel_id: &str,
+        before: Option<String>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Message>> {
+        let before: u64 = before.map_or(u64::MAX, |id| id.parse().expect("Snowflakes are numeric"));
+        let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+        Ok(self
+            .get_messages(channel_id, None, None)?
+            .into_iter()
+            .filter(|msg| msg.id.parse::<u64>().expect("Snowflakes are numeric") < before)
+            .take(limit)
+            .collect())
+    }
+}
+
+impl SendMsgs for FakeMessageGetter {
+    fn send_messages(&self, _message_body: MessageBody) -> Result<SentMessage> {
+        Ok(sent_message())
+    }
+
+    fn send_messages_with_files(
+        &self,
+        _message_body: MessageBody,
+        _files: Vec<FileUpload>,
+    ) -> Result<SentMessage> {
+        Ok(sent_message())
+    }
+}
+
+fn sent_message() -> SentMessage {
+    SentMessage {
+        id: "1".to_string(),
+        channel_id: "out".to_string(),
+    }
+}
+
+/// A transport that records requests and answers each with the next canned response.
+#[derive(Default)]
+pub struct FakeTransport {
+    pub requests: RefCell<Vec<HttpRequest>>,
+    pub responses: RefCell<Vec<HttpResponse>>,
+}
+
+impl FakeTransport {
+    pub fn respond_with(self, status: u16, body: &str) -> Self {
+        self.responses.borrow_mut().push(HttpResponse {
+            status,
+            headers: vec![],
+            body: body.as_bytes().to_vec(),
+        });
+        self
+    }
+}
+
+impl Transport for FakeTransport {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        self.requests.borrow_mut().push(request);
+        let mut responses = self.responses.borrow_mut();
+        if responses.is_empty() {
+            return Err(Error::Static("FakeTransport has no response left"));
+        }
+        Ok(responses.remove(0))
+    }
+}
@@ -0,0 +1,746 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::utils::config::Config;
+use crate::utils::i18n::Locale;
+use crate::utils::message_limits::Overflow;
+use crate::utils::snowflake::snowflake_from_timestamp;
+use crate::utils::template::AnnouncementTemplates;
+
+pub const TOKEN: &str = "Bot test-token";
+
+/// A request the mock received.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    /// The body, for multipart requests the `payload_json` part
+    pub body: String,
+    /// Names and sizes of the files of a multipart request
+    pub files: Vec<(String, usize)>,
+}
+
+#[derive(Default)]
+struct MockState {
+    channels: HashMap<String, Value>,
+    /// Messages per channel, in no particular order.
+    messages: HashMap<String, Vec<Value>>,
+    /// Users that reacted, per `(message_id, emoji)`.
+    reactions: HashMap<(String, String), Vec<Value>>,
+    /// Ids of the pinned messages per channel, most recently pinned first.
+    pins: HashMap<String, Vec<String>>,
+    /// Edits per channel, Discord allows `CHANNEL_EDIT_LIMIT` of them.
+    channel_edits: HashMap<String, usize>,
+    /// Crossposts per channel, Discord allows `CROSSPOST_LIMIT` of them.
+    crossposts: HashMap<String, usize>,
+    /// Roles per `(guild_id, user_id)`.
+    member_roles: HashMap<(String, String), Vec<String>>,
+    /// Downloadable files by path, served without authorization like the CDN does.
+    files: HashMap<String, Vec<u8>>,
+    /// Responses (status, body) served before any routing happens.
+    queued: Vec<(u16, Value)>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// An in-process stand-in for the Discord REST API.
+///
+/// Serves channels, messages (with Discord's `before`/`after`/`limit` pagination), reaction
+/// users and error envelopes on a random local port. Point a `MessageGetter` at it with
+/// `MessageGetter::from_config(&mock.config())`.
+pub struct MockDiscord {
+    pub base_url: String,
+    state: Arc<Mutex<MockState>>,
+    server: Arc<Server>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockDiscord {
+    pub fn start() -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("Could not start mock server"));
+        let base_url = format!("http://{}", server.server_addr());
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let handle = {
+            let server = server.clone();
+            let state = state.clone();
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(&state, request);
+                }
+            })
+        };
+
+        Self {
+            base_url,
+            state,
+            server,
+            handle: Some(handle),
+        }
+    }
+
+    /// A config that points at this server, reading from `in` and posting to `out`.
+    pub fn config(&self) -> Config {
+        Self::config_for(self.base_url.as_str())
+    }
+
+    /// A config for a server at `base_url`, reading from `in` and posting to `out`.
+    pub fn config_for(base_url: &str) -> Config {
+        Config {
+            token: TOKEN.to_string(),
+            client_id: "1".to_string(),
+            base_url: base_url.to_string(),
+            competition_name: "memes".to_string(),
+            in_channel_ids: vec!["in".to_string()],
+            out_channel_id: "out".to_string(),
+            upvote_emoji: "👍".to_string(),
+            channel_awards: false,
+            include_threads: false,
+            reupload_winners: false,
+            upload_max_bytes: 1024 * 1024,
+            archive_dir: None,
+            archive_max_bytes: 1024 * 1024,
+            overflow: Overflow::Split,
+            ping_winners: false,
+            notify_role_id: None,
+            suppress_mentions: true,
+            react_to_winners: false,
+            reply_to_winners: false,
+            pin_winners: false,
+            pin_limit: 45,
+            winner_role_id: None,
+            state_file: state_file(base_url),
+            crosspost: false,
+            topic_channel_id: None,
+            locale: Locale::En,
+            templates: AnnouncementTemplates::for_locale(Locale::En),
+            record_fixtures: None,
+            retry_max_attempts: 3,
+            retry_base_delay_ms: 1,
+            retry_max_delay_ms: 10,
+        }
+    }
+
+    pub fn add_channel(&self, channel: Value) {
+        let id = channel["id"]
+            .as_str()
+            .expect("Channel needs an id")
+            .to_string();
+        self.state.lock().unwrap().channels.insert(id, channel);
+    }
+
+    pub fn add_messages(&self, channel_id: &str, messages: Vec<Value>) {
+        self.state
+            .lock()
+            .unwrap()
+            .messages
+            .entry(channel_id.to_string())
+            .or_default()
+            .extend(messages);
+    }
+
+    /// Serves `data` at `path`, returns the url it can be downloaded from.
+    pub fn add_file(&self, path: &str, data: Vec<u8>) -> String {
+        self.state
+            .lock()
+            .unwrap()
+            .files
+            .insert(path.to_string(), data);
+        format!("{}{path}", self.base_url)
+    }
+
+    pub fn add_reaction_users(&self, message_id: &str, emoji: &str, users: Vec<Value>) {
+        self.state
+            .lock()
+            .unwrap()
+            .reactions
+            .insert((message_id.to_string(), emoji.to_string()), users);
+    }
+
+    /// Answers the next `count` requests with 429 and the given `retry_after` in seconds.
+    pub fn rate_limit_next(&self, count: usize, retry_after: f64) {
+        let body = json!({
+            "message": "You are being rate limited.",
+            "retry_after": retry_after,
+            "global": false,
+        });
+        self.respond_next(count, 429, body);
+    }
+
+    /// Answers the next `count` requests with `status` and `body`, regardless of the route.
+    pub fn respond_next(&self, count: usize, status: u16, body: Value) {
+        let mut state = self.state.lock().unwrap();
+        for _ in 0..count {
+            state.queued.push((status, body.clone()));
+        }
+    }
+
+    /// Pins the messages `message_ids` of `channel_id`, the first one most recently.
+    pub fn add_pins(&self, channel_id: &str, message_ids: Vec<String>) {
+        self.state
+            .lock()
+            .unwrap()
+            .pins
+            .entry(channel_id.to_string())
+            .or_default()
+            .extend(message_ids);
+    }
+
+    /// Ids of the pinned messages of `channel_id`, most recently pinned first.
+    pub fn pins(&self, channel_id: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state.pins.get(channel_id).cloned().unwrap_or_default()
+    }
+
+    pub fn add_member_roles(&self, guild_id: &str, user_id: &str, roles: Vec<String>) {
+        self.state
+            .lock()
+            .unwrap()
+            .member_roles
+            .entry((guild_id.to_string(), user_id.to_string()))
+            .or_default()
+            .extend(roles);
+    }
+
+    /// The roles the member `user_id` of `guild_id` has.
+    pub fn member_roles(&self, guild_id: &str, user_id: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        let key = (guild_id.to_string(), user_id.to_string());
+        state.member_roles.get(&key).cloned().unwrap_or_default()
+    }
+
+    /// The topic of `channel_id`, if it has one.
+    pub fn topic(&self, channel_id: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        let channel = state.channels.get(channel_id)?;
+        channel["topic"].as_str().map(str::to_string)
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Bodies of all messages posted to `channel_id`, oldest first.
+    pub fn posted(&self, channel_id: &str) -> Vec<Value> {
+        let path = format!("/channels/{channel_id}/messages");
+        self.requests()
+            .into_iter()
+            .filter(|req| req.method == "POST" && req.url == path)
+            .map(|req| serde_json::from_str(req.body.as_str()).expect("Posted body is JSON"))
+            .collect()
+    }
+}
+
+impl Drop for MockDiscord {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle(state: &Mutex<MockState>, mut request: Request) {
+    let mut raw_body = Vec::new();
+    let _ = request.as_reader().read_to_end(&mut raw_body);
+    let method = request.method().to_string();
+    let url = request.url().to_string();
+    let header = |name: &str| {
+        request
+            .headers()
+            .iter()
+            .find(|header| header.field.as_str().as_str().eq_ignore_ascii_case(name))
+            .map(|header| header.value.to_string())
+    };
+    let authorized = header("Authorization").is_some_and(|value| value == TOKEN);
+    let boundary = header("Content-Type").and_then(|value| {
+        value
+            .strip_prefix("multipart/form-data; boundary=")
+            .map(str::to_string)
+    });
+    let (body, files) = match boundary {
+        Some(boundary) => parse_multipart(&raw_body, boundary.as_str()),
+        None => (String::from_utf8_lossy(&raw_body).into_owned(), vec![]),
+    };
+
+    let (status, response) = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method: method.clone(),
+            url: url.clone(),
+            body: body.clone(),
+            files,
+        });
+        if let Some(data) = state.files.get(url.as_str()).cloned() {
+            drop(state);
+            let _ = request.respond(Response::from_data(data));
+            return;
+        }
+        if !state.queued.is_empty() {
+            state.queued.remove(0)
+        } else if !authorized {
+            (401, error(0, "401: Unauthorized"))
+        } else {
+            route(&mut state, request.method(), url.as_str(), body.as_str())
+        }
+    };
+
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let mut response = Response::from_string(response.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if status == 429 {
+        let retry_after = Header::from_bytes("Retry-After", "1").unwrap();
+        response = response.with_header(retry_after);
+    }
+    let _ = request.respond(response);
+}
+
+fn route(state: &mut MockState, method: &Method, url: &str, body: &str) -> (u16, Value) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query: HashMap<&str, &str> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+    let segments: Vec<String> = path.trim_matches('/').split('/').map(decode).collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["channels", channel_id]) => match state.channels.get(*channel_id) {
+            Some(channel) => (200, channel.clone()),
+            None => (404, error(10003, "Unknown Channel")),
+        },
+        (Method::Patch, ["channels", channel_id]) => {
+            let edits = state
+                .channel_edits
+                .entry(channel_id.to_string())
+                .or_default();
+            if *edits >= CHANNEL_EDIT_LIMIT {
+                let body = json!({
+                    "message": "You are being rate limited.",
+                    "retry_after": 600.0,
+                    "global": false,
+                });
+                return (429, body);
+            }
+            let Some(channel) = state.channels.get_mut(*channel_id) else {
+                return (404, error(10003, "Unknown Channel"));
+            };
+            *edits += 1;
+            let body: Value = serde_json::from_str(body).unwrap_or_default();
+            if let Some(topic) = body.get("topic") {
+                channel["topic"] = topic.clone();
+            }
+            (200, channel.clone())
+        }
+        (Method::Get, ["channels", channel_id, "messages"]) => {
+            match state.messages.get(*channel_id) {
+                Some(messages) => (200, Value::Array(paginate(messages, &query))),
+                None => (404, error(10003, "Unknown Channel")),
+            }
+        }
+        (Method::Post, ["channels", channel_id, "messages"]) => {
+            match serde_json::from_str::<Value>(body) {
+                Ok(mut message) => {
+                    message["id"] = json!("1");
+                    message["channel_id"] = json!(channel_id);
+                    (200, message)
+                }
+                Err(_) => (400, error(50109, "The request body contains invalid JSON.")),
+            }
+        }
+        (Method::Post, ["channels", channel_id, "messages", _, "crosspost"]) => {
+            let crossposts = state.crossposts.entry(channel_id.to_string()).or_default();
+            if *crossposts >= CROSSPOST_LIMIT {
+                let body = json!({
+                    "message": "You are being rate limited.",
+                    "retry_after": 3600.0,
+                    "global": false,
+                });
+                return (429, body);
+            }
+            *crossposts += 1;
+            match state.channels.get(*channel_id) {
+                Some(channel) if channel["type"] == 5 => (200, Value::Null),
+                Some(_) => (
+                    403,
+                    error(50008, "Cannot send messages in a non-text channel"),
+                ),
+                None => (404, error(10003, "Unknown Channel")),
+            }
+        }
+        (Method::Post, ["attachments", "refresh-urls"]) => {
+            let body: Value = serde_json::from_str(body).unwrap_or_default();
+            let refreshed: Vec<Value> = body["attachment_urls"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(|url| json!({ "original": url, "refreshed": refreshed_url(url) }))
+                .collect();
+            (200, json!({ "refreshed_urls": refreshed }))
+        }
+        (Method::Get, ["channels", channel_id, "messages", message_id]) => {
+            let message = state
+                .messages
+                .get(*channel_id)
+                .and_then(|messages| messages.iter().find(|msg| msg["id"] == *message_id));
+            match message {
+                Some(message) => (200, message.clone()),
+                None => (404, error(10008, "Unknown Message")),
+            }
+        }
+        (Method::Get, ["guilds", guild_id, "threads", "active"]) => {
+            let threads: Vec<Value> = state
+                .channels
+                .values()
+                .filter(|channel| channel["guild_id"] == *guild_id)
+                .filter(|channel| channel["thread_metadata"]["archived"] == json!(false))
+                .cloned()
+                .collect();
+            (200, json!({ "threads": threads, "members": [] }))
+        }
+        (Method::Get, ["channels", channel_id, "threads", "archived", "public"]) => {
+            (200, archived_threads(state, channel_id, &query))
+        }
+        (Method::Get, ["channels", _, "messages", message_id, "reactions", emoji]) => {
+            match state
+                .reactions
+                .get(&(message_id.to_string(), emoji.to_string()))
+            {
+                Some(users) => (200, Value::Array(users.clone())),
+                None => (404, error(10014, "Unknown Emoji")),
+            }
+        }
+        (Method::Put, ["channels", _, "messages", _, "reactions", _, "@me"]) => (204, Value::Null),
+        (Method::Put, ["guilds", guild_id, "members", user_id, "roles", role_id]) => {
+            let roles = state
+                .member_roles
+                .entry((guild_id.to_string(), user_id.to_string()))
+                .or_default();
+            if !roles.iter().any(|role| role == role_id) {
+                roles.push(role_id.to_string());
+            }
+            (204, Value::Null)
+        }
+        (Method::Delete, ["guilds", guild_id, "members", user_id, "roles", role_id]) => {
+            if let Some(roles) = state
+                .member_roles
+                .get_mut(&(guild_id.to_string(), user_id.to_string()))
+            {
+                roles.retain(|role| role != role_id);
+            }
+            (204, Value::Null)
+        }
+        (Method::Get, ["channels", channel_id, "pins"]) => {
+            let pinned: Vec<Value> = state
+                .pins
+                .get(*channel_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| {
+                    state
+                        .messages
+                        .get(*channel_id)
+                        .and_then(|messages| messages.iter().find(|msg| msg["id"] == *id))
+                })
+                .cloned()
+                .collect();
+            (200, Value::Array(pinned))
+        }
+        (Method::Put, ["channels", channel_id, "pins", message_id]) => {
+            let pins = state.pins.entry(channel_id.to_string()).or_default();
+            if pins.len() >= 50 {
+                return (400, error(30003, "Maximum number of pins reached (50)"));
+            }
+            pins.retain(|id| id != message_id);
+            pins.insert(0, message_id.to_string());
+            (204, Value::Null)
+        }
+        (Method::Delete, ["channels", channel_id, "pins", message_id]) => {
+            let pins = state.pins.entry(channel_id.to_string()).or_default();
+            match pins.iter().position(|id| id == message_id) {
+                Some(idx) => {
+                    pins.remove(idx);
+                    (204, Value::Null)
+                }
+                None => (404, error(10008, "Unknown Message")),
+            }
+        }
+        _ => (404, error(0, "404: Not Found")),
+    }
+}
+
+/// Splits a `multipart/form-data` body into the `payload_json` part and the names and sizes of
+/// the file parts.
+fn parse_multipart(body: &[u8], boundary: &str) -> (String, Vec<(String, usize)>) {
+    let delimiter = format!("--{boundary}");
+    let mut payload = String::new();
+    let mut files = Vec::new();
+
+    for part in split_bytes(body, delimiter.as_bytes()) {
+        let Some(header_end) = find_bytes(part, b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&part[..header_end]);
+        let data = &part[header_end + 4..];
+        let data = data.strip_suffix(b"\r\n").unwrap_or(data);
+        let param = |key: &str| {
+            let start = headers.find(format!("{key}=\"").as_str())? + key.len() + 2;
+            let end = headers[start..].find('"')? + start;
+            Some(headers[start..end].to_string())
+        };
+        match (param("name").as_deref(), param("filename")) {
+            (Some("payload_json"), _) => payload = String::from_utf8_lossy(data).into_owned(),
+            (_, Some(filename)) => files.push((filename, data.len())),
+            _ => {}
+        }
+    }
+    (payload, files)
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn split_bytes<'a>(mut haystack: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts = Vec::new();
+    while let Some(idx) = find_bytes(haystack, delimiter) {
+        parts.push(&haystack[..idx]);
+        haystack = &haystack[idx + delimiter.len()..];
+    }
+    parts.push(haystack);
+    parts
+}
+
+/// Archived threads of `parent_id`, most recently archived first, paged by `before` and `limit`.
+fn archived_threads(state: &MockState, parent_id: &str, query: &HashMap<&str, &str>) -> Value {
+    let limit: usize = query
+        .get("limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(50);
+    let archived_at = |thread: &Value| -> DateTime<Utc> {
+        serde_json::from_value(thread["thread_metadata"]["archive_timestamp"].clone()).unwrap()
+    };
+    let before: Option<DateTime<Utc>> = query
+        .get("before")
+        .and_then(|before| DateTime::parse_from_rfc3339(before).ok())
+        .map(|before| before.with_timezone(&Utc));
+
+    let mut threads: Vec<Value> = state
+        .channels
+        .values()
+        .filter(|channel| channel["parent_id"] == *parent_id)
+        .filter(|channel| channel["thread_metadata"]["archived"] == json!(true))
+        .filter(|thread| before.is_none_or(|before| archived_at(thread) < before))
+        .cloned()
+        .collect();
+    threads.sort_by_key(|thread| std::cmp::Reverse(archived_at(thread)));
+    let has_more = threads.len() > limit;
+    threads.truncate(limit);
+    json!({ "threads": threads, "members": [], "has_more": has_more })
+}
+
+/// Applies Discord's history semantics: newest first, `limit` defaults to 50 and is capped at
+/// 100, `before`/`after` return the messages closest to the cursor.
+fn paginate(messages: &[Value], query: &HashMap<&str, &str>) -> Vec<Value> {
+    let limit: usize = query
+        .get("limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(50)
+        .clamp(1, 100);
+    let id_of = |msg: &Value| -> u64 { msg["id"].as_str().unwrap().parse().unwrap() };
+
+    let mut sorted: Vec<Value> = messages.to_vec();
+    sorted.sort_by_key(|msg| std::cmp::Reverse(id_of(msg)));
+
+    if let Some(after) = query.get("after").and_then(|id| id.parse::<u64>().ok()) {
+        let newer: Vec<Value> = sorted
+            .into_iter()
+            .filter(|msg| id_of(msg) > after)
+            .collect();
+        let skip = newer.len().saturating_sub(limit);
+        return newer.into_iter().skip(skip).collect();
+    }
+    if let Some(before) = query.get("before").and_then(|id| id.parse::<u64>().ok()) {
+        return sorted
+            .into_iter()
+            .filter(|msg| id_of(msg) < before)
+            .take(limit)
+            .collect();
+    }
+    sorted.into_iter().take(limit).collect()
+}
+
+/// `url` signed again, valid until the end of 2100.
+/// A state file of its own for the mock at `base_url`, in the temp directory.
+pub fn state_file(base_url: &str) -> String {
+    let port = base_url.rsplit(':').next().unwrap_or_default();
+    std::env::temp_dir()
+        .join(format!("fmr-state-{}-{port}.json", std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+pub fn refreshed_url(url: &str) -> String {
+    let path = url.split('?').next().unwrap_or(url);
+    format!("{path}?ex=f6663900&is=f6663900&hm=fresh&")
+}
+
+/// Edits of a channel Discord allows per ten minutes, the mock never forgets them.
+const CHANNEL_EDIT_LIMIT: usize = 2;
+const CROSSPOST_LIMIT: usize = 10;
+
+fn error(code: i32, message: &str) -> Value {
+    json!({ "code": code, "message": message })
+}
+
+/// Minimal percent-decoding for path segments such as url-encoded emoji.
+fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[idx + 1..idx + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+pub fn user(id: &str, username: &str) -> Value {
+    json!({
+        "id": id,
+        "username": username,
+        "avatar": null,
+        "discriminator": "0",
+        "public_flags": 0,
+        "flags": 0,
+        "banner": null,
+        "accent_color": null,
+        "global_name": username,
+        "avatar_decoration_data": null,
+        "banner_color": null,
+    })
+}
+
+pub fn channel(id: &str, channel_type: i32) -> Value {
+    json!({
+        "id": id,
+        "type": channel_type,
+        "guild_id": "guild",
+        "name": id,
+        "nsfw": false,
+    })
+}
+
+/// Like `message`, but sharing the link `url` that Discord embedded as `embed`.
+pub fn link_message(
+    channel_id: &str,
+    timestamp: DateTime<Utc>,
+    seq: u64,
+    author_id: &str,
+    upvotes: i32,
+    url: &str,
+    embed: Value,
+) -> Value {
+    let mut message = message(channel_id, timestamp, seq, author_id, upvotes);
+    message["content"] = json!(url);
+    message["attachments"] = json!([]);
+    message["embeds"] = json!([embed]);
+    message
+}
+
+/// A thread of `parent_id` created at `created`, archived at `archived` if given.
+pub fn thread(
+    parent_id: &str,
+    created: DateTime<Utc>,
+    archived: Option<DateTime<Utc>>,
+    applied_tags: Vec<&str>,
+) -> Value {
+    let id = snowflake_from_timestamp(&created).to_string();
+    json!({
+        "id": id,
+        "type": 11,
+        "guild_id": "guild",
+        "parent_id": parent_id,
+        "name": id,
+        "thread_metadata": {
+            "archived": archived.is_some(),
+            "auto_archive_duration": 1440,
+            "archive_timestamp": archived.unwrap_or(created).to_rfc3339(),
+            "locked": false,
+        },
+        "applied_tags": applied_tags,
+    })
+}
+
+/// A message with one image attachment posted at `timestamp` with `upvotes` 👍 reactions.
+///
+/// `seq` keeps ids unique for messages posted in the same millisecond.
+pub fn message(
+    channel_id: &str,
+    timestamp: DateTime<Utc>,
+    seq: u64,
+    author_id: &str,
+    upvotes: i32,
+) -> Value {
+    let id = (snowflake_from_timestamp(&timestamp) + seq).to_string();
+    let reactions = if upvotes > 0 {
+        json!([{
+            "emoji": { "id": null, "name": "👍" },
+            "count": upvotes,
+            "count_details": { "burst": 0, "normal": upvotes },
+            "burst_colors": [],
+            "me_burst": false,
+            "burst_me": false,
+            "me": false,
+            "burst_count": 0,
+        }])
+    } else {
+        Value::Null
+    };
+
+    json!({
+        "id": id,
+        "type": 0,
+        "content": "",
+        "channel_id": channel_id,
+        "author": user(author_id, author_id),
+        "attachments": [{
+            "id": id,
+            "filename": "meme.png",
+            "size": 1024,
+            "url": format!("https://cdn.discordapp.com/attachments/{channel_id}/{id}/meme.png"),
+            "proxy_url": format!("https://media.discordapp.net/attachments/{channel_id}/{id}/meme.png"),
+            "width": 100,
+            "height": 100,
+            "content_type": "image/png",
+        }],
+        "embeds": [],
+        "mentions": [],
+        "mention_roles": [],
+        "pinned": false,
+        "mention_everyone": false,
+        "tts": false,
+        "timestamp": timestamp.to_rfc3339(),
+        "edited_timestamp": null,
+        "flags": 0,
+        "components": [],
+        "reactions": reactions,
+    })
+}
@@ -1,31 +1,1862 @@
-use chrono::{Datelike, Utc};
+use std::collections::HashMap;
 
-use fakes::FakeMessageGetter;
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::json;
 
-use crate::discord_api::messages::{GetMsgs, MessageService};
-use crate::sort_messages_by_upvote;
+use fakes::{FakeMessageGetter, FakeTransport};
+use mock_discord::MockDiscord;
+
+use crate::discord_api::async_messages::{AsyncMessageGetter, AsyncMessageService};
+use crate::discord_api::fixtures::{Recorder, Replayer};
+use crate::discord_api::history::{history_stream, MessageHistory, Order};
+use crate::discord_api::messages::{GetMsgs, MessageGetter, MessageService, SendMsgs};
+use crate::discord_api::retry::RetryPolicy;
+use crate::discord_api::transport::{Body, Method, ReqwestTransport, Transport};
+use crate::discord_api::uploads::{multipart_parts, DownloadFiles, FileUpload};
+use crate::models::archive::ArchiveEntry;
+use crate::models::discord::{Embed, Message, SentMessage};
+use crate::models::state::{BotState, PinnedWinner, RoleAction, RoleHolder};
+use crate::prelude::*;
+use crate::utils::archive::sha256_hex;
+use crate::utils::cdn_expiry::{cdn_expiry, is_expired};
+use crate::utils::clock::FixedClock;
+use crate::utils::competition::Competition;
+use crate::utils::embed_builder::EmbedBuilder;
+use crate::utils::i18n::Locale;
+use crate::utils::last_month_date::last_month_date;
+use crate::utils::media::{meme_media, MediaKind};
+use crate::utils::message_body_builder::MessageBodyBuilder;
+use crate::utils::message_limits::{validate, Overflow};
+use crate::utils::period::Period;
+use crate::utils::state::StateFile;
+use crate::utils::template::{AnnouncementTemplates, Template};
+use crate::{announce, sort_messages_by_upvote};
 
 mod fakes;
+mod mock_discord;
+
+/// The data in `data/msgs.json` was captured in early April 2024.
+fn fixed_clock() -> FixedClock {
+    FixedClock::new(Utc.with_ymd_and_hms(2024, 4, 2, 12, 30, 0).unwrap())
+}
+
+#[test]
+fn test_last_month_date() {
+    let result = last_month_date(&fixed_clock()).expect("???");
+
+    assert_eq!(result, Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+}
+
+#[test]
+fn test_last_month_date_across_year_boundary() {
+    let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 31, 23, 59, 59).unwrap());
+
+    let result = last_month_date(&clock).expect("???");
+
+    assert_eq!(result, Utc.with_ymd_and_hms(2023, 12, 1, 0, 0, 0).unwrap());
+}
 
 #[test]
 fn test_get_memes_for_month() {
     let struct_to_test = MessageService::new(FakeMessageGetter {});
-    let month = Utc::now().with_day(1).expect("???"); // TODO: static date
+    let month = last_month_date(&fixed_clock()).expect("???");
 
-    let result = struct_to_test.get_memes_for_month(&month);
+    let result = struct_to_test
+        .get_memes_for_month("in", &month)
+        .expect("???");
 
     assert_eq!(result.len(), 5)
 }
 #[test]
 fn test_sort_messages_by_upvote() {
     let struct_to_test = MessageService::new(FakeMessageGetter {});
-    let month = Utc::now().with_day(1).expect("???"); // TODO: static date
 
-    let all_msgs = struct_to_test.client.get_messages(None, None).expect("???");
+    let all_msgs = struct_to_test
+        .client
+        .get_messages("in", None, None)
+        .expect("???");
 
-    let sorted_msgs = sort_messages_by_upvote(all_msgs.iter().collect());
+    let sorted_msgs = sort_messages_by_upvote(all_msgs.iter().collect(), "👍");
 
     assert_eq!(sorted_msgs.len(), 2);
-    assert_eq!(sorted_msgs[0].id, "1213768968272085042");
-    assert_eq!(sorted_msgs[1].id, "1215354063861055520");
+    assert_eq!(sorted_msgs[0].id, "1215354063861055520");
+    assert_eq!(sorted_msgs[1].id, "1213768968272085042");
+}
+
+/// One message every six hours from mid February to mid April 2024, upvotes cycling 0..7.
+fn seed_history(mock: &MockDiscord) -> usize {
+    let start = Utc.with_ymd_and_hms(2024, 2, 15, 0, 0, 0).unwrap();
+    let msgs: Vec<_> = (0..240)
+        .map(|idx| {
+            let timestamp = start + Duration::hours(6 * idx);
+            mock_discord::message(
+                "in",
+                timestamp,
+                0,
+                format!("{}", idx % 5).as_str(),
+                (idx % 7) as i32,
+            )
+        })
+        .collect();
+    mock.add_messages("in", msgs);
+    31 * 4
+}
+
+#[test]
+fn test_get_memes_for_month_paginates_against_mock() {
+    let mock = MockDiscord::start();
+    let expected = seed_history(&mock);
+    let struct_to_test = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    let result = struct_to_test
+        .get_memes_for_month("in", &month)
+        .expect("???");
+
+    assert_eq!(result.len(), expected);
+    assert!(result.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    let pages = mock
+        .requests()
+        .iter()
+        .filter(|req| req.method == "GET")
+        .count();
+    assert!(pages >= 2, "expected several pages, got {pages}");
+}
+
+#[test]
+fn test_get_messages_waits_out_rate_limit() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    mock.rate_limit_next(2, 0.01);
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let result = getter.get_messages("in", None, Some(5)).expect("???");
+
+    assert_eq!(result.len(), 5);
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[test]
+fn test_get_messages_surfaces_error_envelope() {
+    let mock = MockDiscord::start();
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let result = getter.get_messages("in", None, Some(5));
+
+    match result {
+        Err(Error::Discord { status, code, .. }) => {
+            assert_eq!(status, 404);
+            assert_eq!(code, 10003);
+        }
+        other => panic!("expected Discord error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_announce_posts_top_three() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&mock.config()),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let posted = mock.posted("out");
+    assert_eq!(posted.len(), 1);
+    let embeds = posted[0]["embeds"].as_array().expect("embeds");
+    assert_eq!(embeds.len(), 3);
+    assert_eq!(embeds[0]["title"], json!("1."));
+    assert!(embeds[0]["description"]
+        .as_str()
+        .unwrap()
+        .contains("With 6 upvotes"));
+}
+
+#[test]
+fn test_announce_without_upvotes_posts_nothing() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    mock.add_messages(
+        "in",
+        vec![mock_discord::message("in", timestamp, 0, "1", 0)],
+    );
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&mock.config()),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    assert!(mock.posted("out").is_empty());
+}
+
+#[test]
+fn test_get_memes_for_channels_merges_channels() {
+    let mock = MockDiscord::start();
+    let expected = seed_history(&mock);
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    mock.add_messages(
+        "in2",
+        vec![
+            mock_discord::message("in2", timestamp, 1, "7", 9),
+            mock_discord::message("in2", timestamp, 2, "8", 1),
+        ],
+    );
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    let result = message_service
+        .get_memes_for_channels(&["in".to_string(), "in2".to_string()], &month)
+        .expect("???");
+
+    assert_eq!(result.len(), expected + 2);
+    assert!(result.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+    assert_eq!(
+        result.iter().filter(|msg| msg.channel_id == "in2").count(),
+        2
+    );
+}
+
+#[test]
+fn test_announce_posts_rich_winner_embeds() {
+    let mock = MockDiscord::start();
+    mock.add_channel(mock_discord::channel("in", 0));
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let mut first = mock_discord::message("in", timestamp, 0, "1", 6);
+    first["author"]["global_name"] = json!("Meme Lord");
+    first["author"]["avatar"] = json!("a_1234");
+    first["reactions"][0]["count_details"] = json!({ "burst": 2, "normal": 4 });
+    let first_id = first["id"].as_str().unwrap().to_string();
+    let mut second = mock_discord::message("in", timestamp, 1, "4194304", 3);
+    second["author"]["global_name"] = json!(null);
+    mock.add_messages("in", vec![first, second]);
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&mock.config()),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let embeds = mock.posted("out")[0]["embeds"].clone();
+    assert_eq!(embeds[0]["color"], json!(0xFFD700));
+    assert_eq!(embeds[1]["color"], json!(0xC0C0C0));
+    assert_eq!(
+        embeds[0]["author"],
+        json!({
+            "name": "Meme Lord",
+            "icon_url": "https://cdn.discordapp.com/avatars/1/a_1234.gif",
+        })
+    );
+    assert_eq!(embeds[1]["author"]["name"], json!("4194304"));
+    assert_eq!(
+        embeds[1]["author"]["icon_url"],
+        json!("https://cdn.discordapp.com/embed/avatars/1.png")
+    );
+    assert_eq!(
+        embeds[0]["url"],
+        json!(format!("https://discord.com/channels/guild/in/{first_id}"))
+    );
+    assert_eq!(embeds[0]["timestamp"], json!("2024-03-10T12:00:00Z"));
+    assert_eq!(
+        embeds[0]["fields"],
+        json!([{ "name": "Score", "value": "6 (4 + 2 super)", "inline": true }])
+    );
+    assert_eq!(embeds[1]["fields"][0]["value"], json!("3"));
+}
+
+#[test]
+fn test_template_renders_variables_and_escapes() {
+    let template: Template = "{{{place}}} {author} won {period}!".parse().unwrap();
+    let vars = HashMap::from([
+        ("place", "1.".to_string()),
+        ("author", "<@1>".to_string()),
+        ("period", "March 2024".to_string()),
+    ]);
+
+    assert_eq!(template.render(&vars).unwrap(), "{1.} <@1> won March 2024!");
+    let err = "{score}"
+        .parse::<Template>()
+        .unwrap()
+        .render(&vars)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Unknown variable {score}"),
+        "{err}"
+    );
+    assert!(AnnouncementTemplates::for_locale(Locale::De)
+        .validate()
+        .is_ok());
+    let mut templates = AnnouncementTemplates::for_locale(Locale::En);
+    templates.topic = "{author} is champion".parse().unwrap();
+    let err = templates.validate().unwrap_err();
+    assert!(err.to_string().contains("{author}"), "{err}");
+    assert!("{place".parse::<Template>().is_err());
+    assert!("place}".parse::<Template>().is_err());
+}
+
+#[test]
+fn test_announce_renders_competition_templates() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    let mut config = mock.config();
+    config.competition_name = "Fnuef".to_string();
+    config.templates = AnnouncementTemplates {
+        intro: "{competition}: the {count} best memes of {period}"
+            .parse()
+            .unwrap(),
+        title: "#{place} {author_name}".parse().unwrap(),
+        description: "{score} votes in {channel}".parse().unwrap(),
+        footer: "{link}".parse().unwrap(),
+        topic: "{author_name} rules {competition}".parse().unwrap(),
+    };
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let posted = mock.posted("out");
+    assert_eq!(
+        posted[0]["content"],
+        json!("Fnuef: the 3 best memes of March 2024")
+    );
+    let embed = &posted[0]["embeds"][0];
+    assert!(embed["title"].as_str().unwrap().starts_with("#1. "));
+    assert_eq!(embed["description"], json!("6 votes in <#in>"));
+    assert_eq!(embed["footer"]["text"], embed["url"]);
+}
+
+#[test]
+fn test_period_names_months_in_locale() {
+    let period = Period::month_of(&Utc.with_ymd_and_hms(2024, 3, 15, 0, 0, 0).unwrap()).unwrap();
+
+    assert_eq!(period.name(Locale::En).unwrap(), "March 2024");
+    assert_eq!(period.name(Locale::De).unwrap(), "März 2024");
+    assert_eq!("de-DE".parse::<Locale>().unwrap(), Locale::De);
+    assert!("fr".parse::<Locale>().is_err());
+}
+
+#[test]
+fn test_announce_in_competition_locale() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    let mut config = mock.config();
+    config.locale = Locale::De;
+    config.templates = AnnouncementTemplates::for_locale(Locale::De);
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let posted = mock.posted("out");
+    assert_eq!(
+        posted[0]["content"],
+        json!("Die 3 besten Memes im März 2024:")
+    );
+    let embed = &posted[0]["embeds"][0];
+    assert!(embed["description"]
+        .as_str()
+        .unwrap()
+        .ends_with("\nMit 6 Upvotes"));
+    assert_eq!(embed["fields"][0]["name"], json!("Punkte"));
+}
+
+#[test]
+fn test_announce_restricts_mentions() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    mock.add_messages(
+        "in",
+        vec![
+            mock_discord::message("in", timestamp, 0, "1", 3),
+            mock_discord::message("in", timestamp, 1, "2", 2),
+            mock_discord::message("in", timestamp, 2, "1", 1),
+        ],
+    );
+    let mut config = mock.config();
+    config.templates.intro = "@everyone, the winners of {period}:".parse().unwrap();
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    for ping in [false, true] {
+        config.ping_winners = ping;
+        config.notify_role_id = ping.then(|| "42".to_string());
+        let message_service = MessageService::new(MessageGetter::from_config(&config));
+        announce(
+            &message_service,
+            &Competition::from_config(&config),
+            &month,
+            &fixed_clock(),
+        )
+        .expect("???");
+    }
+
+    let posted = mock.posted("out");
+    assert_eq!(
+        posted[0]["content"],
+        json!("@everyone, the winners of March 2024:")
+    );
+    assert_eq!(
+        posted[0]["allowed_mentions"],
+        json!({ "parse": [], "replied_user": false })
+    );
+    assert_eq!(
+        posted[1]["content"],
+        json!("@everyone, the winners of March 2024:\n<@&42> <@1> <@2>")
+    );
+    assert_eq!(
+        posted[1]["allowed_mentions"],
+        json!({ "parse": [], "users": ["1", "2"], "roles": ["42"], "replied_user": false })
+    );
+}
+
+#[test]
+fn test_announce_reacts_and_replies_to_winners() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let first = mock_discord::message("in", timestamp, 0, "1", 3);
+    let second = mock_discord::message("in", timestamp, 1, "2", 2);
+    let ids = [first["id"].clone(), second["id"].clone()];
+    mock.add_messages("in", vec![first, second]);
+    let mut config = mock.config();
+    config.react_to_winners = true;
+    config.reply_to_winners = true;
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let reactions: Vec<String> = mock
+        .requests()
+        .into_iter()
+        .filter(|req| req.method == "PUT")
+        .map(|req| req.url)
+        .collect();
+    assert_eq!(
+        reactions,
+        vec![
+            format!(
+                "/channels/in/messages/{}/reactions/%F0%9F%A5%87/@me",
+                ids[0].as_str().unwrap()
+            ),
+            format!(
+                "/channels/in/messages/{}/reactions/%F0%9F%A5%88/@me",
+                ids[1].as_str().unwrap()
+            ),
+        ]
+    );
+    let replies = mock.posted("in");
+    assert_eq!(replies.len(), 2);
+    assert_eq!(
+        replies[0]["content"],
+        json!("🏆 1. in the meme competition of March 2024!")
+    );
+    assert_eq!(
+        replies[0]["message_reference"],
+        json!({ "message_id": ids[0], "channel_id": "in", "fail_if_not_exists": false })
+    );
+    assert_eq!(replies[0]["allowed_mentions"]["replied_user"], json!(false));
+}
+
+#[test]
+fn test_announce_pins_winner_and_rotates_old_pins() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let winner = mock_discord::message("in", timestamp, 0, "1", 3);
+    let winner_id = winner["id"].as_str().unwrap().to_string();
+    let pinned: Vec<serde_json::Value> = (1..=45)
+        .map(|seq| mock_discord::message("in", timestamp, seq, "2", 0))
+        .collect();
+    let pinned_ids: Vec<String> = pinned
+        .iter()
+        .map(|msg| msg["id"].as_str().unwrap().to_string())
+        .collect();
+    mock.add_messages("in", pinned);
+    mock.add_messages("in", vec![winner]);
+    mock.add_pins("in", pinned_ids.iter().rev().cloned().collect());
+    let mut config = mock.config();
+    config.pin_winners = true;
+    let pinned_by_bot = |idx: usize| PinnedWinner {
+        competition: "memes".to_string(),
+        channel_id: "in".to_string(),
+        message_id: pinned_ids[idx].clone(),
+        pinned_at: timestamp - Duration::days(60 - idx as i64),
+    };
+    let state_file = StateFile::new(config.state_file.as_str());
+    let state = BotState {
+        pinned_winners: vec![pinned_by_bot(0), pinned_by_bot(1)],
+        ..Default::default()
+    };
+    state_file.save(&state).unwrap();
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let pins = mock.pins("in");
+    assert_eq!(pins.len(), 45);
+    assert_eq!(pins[0], winner_id);
+    assert!(!pins.contains(&pinned_ids[0]));
+    let ledger: Vec<String> = state_file
+        .load()
+        .unwrap()
+        .pinned_winners
+        .into_iter()
+        .map(|winner| winner.message_id)
+        .collect();
+    std::fs::remove_file(config.state_file.as_str()).unwrap();
+    assert_eq!(ledger, vec![pinned_ids[1].clone(), winner_id]);
+}
+
+#[test]
+fn test_announce_names_winner_in_topic() {
+    let mock = MockDiscord::start();
+    mock.add_channel(mock_discord::channel("in", 0));
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    mock.add_messages(
+        "in",
+        vec![
+            mock_discord::message("in", timestamp, 0, "1", 3),
+            mock_discord::message("in", timestamp, 1, "2", 2),
+        ],
+    );
+    let mut config = mock.config();
+    config.topic_channel_id = Some("in".to_string());
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    assert_eq!(
+        mock.topic("in").as_deref(),
+        Some("Current Meme Lord: 1 — March 2024")
+    );
+}
+
+#[test]
+fn test_announce_crossposts_in_announcement_channel() {
+    for (channel_type, crosspost, expected) in [(5, true, 1), (5, false, 0), (0, true, 0)] {
+        let mock = MockDiscord::start();
+        seed_history(&mock);
+        mock.add_channel(mock_discord::channel("out", channel_type));
+        let mut config = mock.config();
+        config.crosspost = crosspost;
+        let message_service = MessageService::new(MessageGetter::from_config(&config));
+        let month = last_month_date(&fixed_clock()).expect("???");
+
+        announce(
+            &message_service,
+            &Competition::from_config(&config),
+            &month,
+            &fixed_clock(),
+        )
+        .expect("???");
+
+        let crossposts: Vec<String> = mock
+            .requests()
+            .into_iter()
+            .filter(|req| req.method == "POST" && req.url.ends_with("/crosspost"))
+            .map(|req| req.url)
+            .collect();
+        assert_eq!(crossposts.len(), expected, "type {channel_type}");
+        if expected > 0 {
+            assert_eq!(crossposts[0], "/channels/out/messages/1/crosspost");
+        }
+    }
+}
+
+#[test]
+fn test_publish_stops_at_rate_limit() {
+    let mock = MockDiscord::start();
+    mock.add_channel(mock_discord::channel("out", 5));
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let sent: Vec<SentMessage> = (0..12)
+        .map(|idx| SentMessage {
+            id: idx.to_string(),
+            channel_id: "out".to_string(),
+        })
+        .collect();
+
+    assert_eq!(message_service.publish(&sent).unwrap(), 10);
+    let crossposts = mock
+        .requests()
+        .into_iter()
+        .filter(|req| req.url.ends_with("/crosspost"))
+        .count();
+    // Nothing is attempted after the first rate limited crosspost.
+    assert_eq!(crossposts, 10 + 1);
+}
+
+#[test]
+fn test_update_topic_respects_rate_limit() {
+    let mock = MockDiscord::start();
+    mock.add_channel(mock_discord::channel("in", 0));
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+
+    let patches = || {
+        mock.requests()
+            .into_iter()
+            .filter(|req| req.method == "PATCH")
+            .count()
+    };
+    let mut patches_per_call = Vec::new();
+    for (topic, changed) in [
+        ("First", true),
+        ("First", false),
+        ("Second", true),
+        ("Third", false),
+    ] {
+        let before = patches();
+        assert_eq!(message_service.update_topic("in", topic).unwrap(), changed);
+        patches_per_call.push(patches() - before);
+    }
+
+    assert_eq!(mock.topic("in").as_deref(), Some("Second"));
+    // The unchanged topic is not sent, the rate limited one is not retried.
+    assert_eq!(patches_per_call, [1, 0, 1, 1]);
+}
+
+#[test]
+fn test_announce_passes_on_winner_role() {
+    let mock = MockDiscord::start();
+    mock.add_channel(mock_discord::channel("in", 0));
+    mock.add_member_roles("guild", "7", vec!["lord".to_string()]);
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    mock.add_messages(
+        "in",
+        vec![
+            mock_discord::message("in", timestamp, 0, "1", 3),
+            mock_discord::message("in", timestamp, 1, "2", 2),
+        ],
+    );
+    let mut config = mock.config();
+    config.winner_role_id = Some("lord".to_string());
+    let state_file = StateFile::new(config.state_file.as_str());
+    let state = BotState {
+        role_holders: vec![RoleHolder {
+            competition: "memes".to_string(),
+            guild_id: "guild".to_string(),
+            role_id: "lord".to_string(),
+            user_id: "7".to_string(),
+        }],
+        ..Default::default()
+    };
+    state_file.save(&state).unwrap();
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let state = state_file.load().unwrap();
+    std::fs::remove_file(config.state_file.as_str()).unwrap();
+    assert!(mock.member_roles("guild", "7").is_empty());
+    assert_eq!(mock.member_roles("guild", "1"), vec!["lord".to_string()]);
+    assert_eq!(state.role_holders.len(), 1);
+    assert_eq!(state.role_holders[0].user_id, "1");
+    let audit: Vec<(RoleAction, String)> = state
+        .audit_log
+        .into_iter()
+        .map(|entry| (entry.action, entry.user_id))
+        .collect();
+    assert_eq!(
+        audit,
+        vec![
+            (RoleAction::Revoked, "7".to_string()),
+            (RoleAction::Granted, "1".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_announce_with_channel_awards() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    mock.add_messages(
+        "in2",
+        vec![mock_discord::message("in2", timestamp, 1, "7", 2)],
+    );
+    let mut config = mock.config();
+    config.in_channel_ids = vec!["in".to_string(), "in2".to_string()];
+    config.channel_awards = true;
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let posted = mock.posted("out");
+    assert_eq!(posted.len(), 1);
+    let embeds = posted[0]["embeds"].as_array().expect("embeds");
+    assert_eq!(embeds.len(), 4);
+    assert_eq!(embeds[0]["fields"][0]["value"], json!("<#in>"));
+    assert_eq!(embeds[3]["fields"][0]["value"], json!("<#in2>"));
+    assert_eq!(embeds[3]["title"], json!("Best in channel"));
+}
+
+#[test]
+fn test_channel_award_skips_channels_on_the_podium() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    for (channel_id, seq, upvotes) in [("in", 0, 5), ("in2", 1, 4), ("in3", 2, 3), ("in", 3, 2)] {
+        mock.add_messages(
+            channel_id,
+            vec![mock_discord::message(
+                channel_id, timestamp, seq, "7", upvotes,
+            )],
+        );
+    }
+    mock.add_messages(
+        "in4",
+        vec![mock_discord::message("in4", timestamp, 4, "7", 1)],
+    );
+    let mut config = mock.config();
+    config.in_channel_ids = ["in", "in2", "in3", "in4"].map(str::to_string).to_vec();
+    config.channel_awards = true;
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let posted = mock.posted("out");
+    let embeds = posted[0]["embeds"].as_array().expect("embeds");
+    let channels: Vec<_> = embeds
+        .iter()
+        .map(|embed| embed["fields"][0]["value"].clone())
+        .collect();
+    assert_eq!(
+        channels,
+        vec![
+            json!("<#in>"),
+            json!("<#in2>"),
+            json!("<#in3>"),
+            json!("<#in4>")
+        ]
+    );
+    assert_eq!(embeds[3]["title"], json!("Best in channel"));
+}
+
+#[test]
+fn test_replayed_fixture_parses() {
+    let config = MockDiscord::config_for("http://replay");
+    let replayer = MessageGetter::with_transport(
+        &config,
+        Replayer::new("src/tests/data/fixtures", config.base_url.as_str()),
+    );
+
+    let msgs = replayer
+        .get_messages("795984622399782912", None, Some(100))
+        .expect("???");
+    let sorted_msgs = sort_messages_by_upvote(msgs.iter().collect(), "👍");
+
+    assert_eq!(msgs.len(), 15);
+    assert_eq!(sorted_msgs.len(), 2);
+    assert_eq!(sorted_msgs[0].author.username, "user1");
+}
+
+#[test]
+fn test_recorder_scrubs_token_and_users() {
+    let recorder = Recorder::new(std::env::temp_dir(), "Bot secret");
+    let body = json!([{
+        "content": "<@290158909854056448> leaked Bot secret in private",
+        "author": mock_discord::user("290158909854056448", "sir.mom"),
+        "attachments": [{
+            "id": "7",
+            "filename": "holiday_sir_mom.png",
+            "url": "https://cdn.discordapp.com/holiday_sir_mom.png?ex=65fc71ac&is=65e9fcac&hm=c4023094&",
+        }],
+        "embeds": [{
+            "title": "Private post",
+            "thumbnail": { "url": "https://cdn.discordapp.com/a.png?hm=5ec7e7&x=1&hm=0dd5ec7&" },
+        }],
+    }]);
+
+    let sanitized = recorder.sanitize(body).to_string();
+
+    assert!(!sanitized.contains("secret"));
+    assert!(!sanitized.contains("290158909854056448"));
+    assert!(!sanitized.contains("sir.mom"));
+    assert!(!sanitized.contains("c4023094"));
+    assert!(!sanitized.contains("5ec7e7"));
+    assert!(!sanitized.contains("0dd5ec7"));
+    assert!(!sanitized.contains("private"));
+    assert!(!sanitized.contains("Private"));
+    assert!(!sanitized.contains("holiday"));
+    assert!(sanitized.contains("<@100000000000000001> [redacted]"));
+    assert!(sanitized.contains("https://cdn.discordapp.com/7.png?"));
+}
+
+#[test]
+fn test_recorded_run_replays_offline() {
+    let mock = MockDiscord::start();
+    let expected = seed_history(&mock);
+    let dir = std::env::temp_dir().join(format!("fmr-fixtures-{}", std::process::id()));
+    let mut config = mock.config();
+    config.record_fixtures = Some(dir.to_string_lossy().into_owned());
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    let recorded = MessageService::new(MessageGetter::from_config(&config))
+        .get_memes_for_month("in", &month)
+        .expect("???");
+    let replayer = Replayer::new(&dir, config.base_url.as_str());
+    let replayed = MessageService::new(MessageGetter::with_transport(&config, replayer))
+        .get_memes_for_month("in", &month)
+        .expect("???");
+    std::fs::remove_dir_all(&dir).expect("???");
+
+    assert_eq!(recorded.len(), expected);
+    assert_eq!(replayed.len(), expected);
+    assert!(replayed
+        .iter()
+        .all(|msg| msg.author.username.starts_with("user")));
+}
+
+#[test]
+fn test_message_getter_sends_through_transport() {
+    let transport = FakeTransport::default()
+        .respond_with(200, "[]")
+        .respond_with(200, r#"{"id":"1","channel_id":"out"}"#);
+    let getter = MessageGetter::with_transport(&MockDiscord::config_for("http://fake"), transport);
+
+    getter
+        .get_messages("in", Some("42".to_string()), Some(10))
+        .expect("???");
+    getter
+        .send_messages(MessageBodyBuilder::new("hi").build())
+        .expect("???");
+
+    let requests = getter.transport.requests.borrow();
+    assert_eq!(requests[0].method, Method::Get);
+    assert_eq!(
+        requests[0].url,
+        "http://fake/channels/in/messages?after=42&limit=10"
+    );
+    assert_eq!(requests[1].method, Method::Post);
+    assert_eq!(requests[1].url, "http://fake/channels/out/messages");
+    for request in requests.iter() {
+        let auth: Vec<_> = request
+            .headers
+            .iter()
+            .filter(|(name, _)| name == "Authorization")
+            .collect();
+        assert_eq!(
+            auth,
+            vec![&("Authorization".to_string(), mock_discord::TOKEN.to_string())]
+        );
+    }
+    assert!(
+        matches!(&requests[1].body, Some(Body::Json(json)) if json.contains("\"content\":\"hi\""))
+    );
+}
+
+#[test]
+fn test_get_is_retried_on_server_error() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    mock.respond_next(2, 502, json!({ "code": 0, "message": "Bad Gateway" }));
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let result = getter.get_messages("in", None, Some(5)).expect("???");
+
+    assert_eq!(result.len(), 5);
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[test]
+fn test_post_is_not_retried_on_server_error() {
+    let mock = MockDiscord::start();
+    mock.respond_next(
+        1,
+        500,
+        json!({ "code": 0, "message": "Internal Server Error" }),
+    );
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let result = getter.send_messages(MessageBodyBuilder::new("hi").build());
+
+    assert!(matches!(result, Err(Error::Discord { status: 500, .. })));
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn test_retries_give_up_after_max_attempts() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    mock.rate_limit_next(5, 0.001);
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let result = getter.get_messages("in", None, Some(5));
+
+    assert!(matches!(result, Err(Error::RateLimited { .. })));
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[test]
+fn test_long_rate_limit_is_not_retried() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    mock.rate_limit_next(1, 600.0);
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let result = getter.get_messages("in", None, Some(5));
+
+    assert!(matches!(result, Err(Error::RateLimited { retry_after, .. }) if retry_after == 600.0));
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn test_retry_backoff_doubles_up_to_max_delay() {
+    let policy = RetryPolicy {
+        max_attempts: 10,
+        base_delay: std::time::Duration::from_millis(100),
+        max_delay: std::time::Duration::from_millis(500),
+        jitter: false,
+    };
+
+    let delays: Vec<u128> = (1..=5)
+        .map(|attempt| policy.backoff(attempt).as_millis())
+        .collect();
+
+    assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+    let jittered = RetryPolicy {
+        jitter: true,
+        ..policy
+    }
+    .backoff(2)
+    .as_millis();
+    assert!((100..=200).contains(&jittered));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_async_get_memes_for_month_matches_blocking() {
+    let mock = MockDiscord::start();
+    let expected = seed_history(&mock);
+    let service = AsyncMessageService::new(AsyncMessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    let result = service
+        .get_memes_for_month("in", &month)
+        .await
+        .expect("???");
+
+    assert_eq!(result.len(), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_async_reaction_users_are_fetched_for_every_message() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    let service = AsyncMessageService::new(AsyncMessageGetter::from_config(&mock.config()))
+        .with_max_concurrency(3);
+    let month = last_month_date(&fixed_clock()).expect("???");
+    let msgs = service
+        .get_memes_for_month("in", &month)
+        .await
+        .expect("???");
+    let msgs = &msgs[..10];
+    for msg in msgs {
+        let users = vec![
+            mock_discord::user("7", "seven"),
+            mock_discord::user("8", "eight"),
+        ];
+        mock.add_reaction_users(msg.id.as_str(), "👍", users);
+    }
+
+    let result = service.get_reaction_users(msgs, "👍").await.expect("???");
+
+    assert_eq!(result.len(), 10);
+    assert!(result.values().all(|users| users.len() == 2));
+}
+
+#[test]
+fn test_period_month_of() {
+    let date = Utc.with_ymd_and_hms(2024, 12, 24, 18, 0, 0).unwrap();
+
+    let period = Period::month_of(&date).expect("???");
+
+    assert_eq!(
+        period.start,
+        Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        period.end,
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()
+    );
+    assert!(period.contains(&period.start));
+    assert!(!period.contains(&period.end));
+}
+
+#[test]
+fn test_history_newest_first_walks_all_pages() {
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let msgs: Vec<_> = MessageHistory::newest_first(&getter, "in")
+        .page_size(50)
+        .collect::<Result<_>>()
+        .expect("???");
+
+    assert_eq!(msgs.len(), 240);
+    assert!(msgs.windows(2).all(|w| w[0].timestamp > w[1].timestamp));
+    assert_eq!(mock.requests().len(), 5);
+}
+
+#[test]
+fn test_history_take_while_stops_at_period_end() {
+    let mock = MockDiscord::start();
+    let expected = seed_history(&mock);
+    let getter = MessageGetter::from_config(&mock.config());
+    let period = Period::last_month(&fixed_clock()).expect("???");
+
+    let msgs: Vec<_> = MessageHistory::since(&getter, "in", &period.start)
+        .page_size(10)
+        .take_while(|msg| {
+            msg.as_ref()
+                .map_or(true, |msg| period.contains(&msg.timestamp))
+        })
+        .collect::<Result<_>>()
+        .expect("???");
+
+    assert_eq!(msgs.len(), expected);
+    assert_eq!(mock.requests().len(), expected / 10 + 1);
+}
+
+#[test]
+fn test_history_stops_on_error() {
+    let mock = MockDiscord::start();
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let results: Vec<_> = MessageHistory::newest_first(&getter, "in").collect();
+
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_history_stream_newest_first() {
+    use futures::TryStreamExt;
+
+    let mock = MockDiscord::start();
+    seed_history(&mock);
+    let getter = AsyncMessageGetter::from_config(&mock.config());
+
+    let msgs: Vec<_> = history_stream(&getter, "in", Order::NewestFirst, None)
+        .try_collect()
+        .await
+        .expect("???");
+
+    assert_eq!(msgs.len(), 240);
+    assert!(msgs.windows(2).all(|w| w[0].timestamp > w[1].timestamp));
+}
+
+#[test]
+fn test_get_memes_for_competition_includes_threads() {
+    let mock = MockDiscord::start();
+    let expected = seed_history(&mock);
+    mock.add_channel(mock_discord::channel("in", 0));
+    let at = |day: u32| Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+    let active = mock_discord::thread("in", at(5), None, vec![]);
+    let archived = mock_discord::thread("in", at(1) - Duration::days(10), Some(at(3)), vec![]);
+    let stale = mock_discord::thread(
+        "in",
+        at(1) - Duration::days(30),
+        Some(at(1) - Duration::days(20)),
+        vec![],
+    );
+    let future = mock_discord::thread(
+        "in",
+        Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap(),
+        None,
+        vec![],
+    );
+    for thread in [&active, &archived, &stale, &future] {
+        mock.add_channel(thread.clone());
+    }
+    let active_id = active["id"].as_str().unwrap();
+    let archived_id = archived["id"].as_str().unwrap();
+    mock.add_messages(
+        active_id,
+        vec![
+            mock_discord::message(active_id, at(6), 1, "7", 3),
+            mock_discord::message(active_id, at(7), 1, "8", 1),
+        ],
+    );
+    mock.add_messages(
+        archived_id,
+        vec![
+            mock_discord::message(archived_id, at(1) - Duration::days(5), 1, "7", 3),
+            mock_discord::message(archived_id, at(2), 1, "9", 4),
+        ],
+    );
+    let mut config = mock.config();
+    config.include_threads = true;
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    let result = message_service
+        .get_memes_for_competition(&Competition::from_config(&config), &month)
+        .expect("???");
+
+    assert_eq!(result.len(), expected + 3);
+    assert!(result.iter().all(|msg| msg.source_channel_id() == "in"));
+    assert!(result
+        .iter()
+        .all(|msg| msg.guild_id.as_deref() == Some("guild")));
+    assert_eq!(
+        result.iter().filter(|msg| msg.channel_id != "in").count(),
+        3
+    );
+    let fetched_messages_of = |thread: &serde_json::Value| {
+        let path = format!("/channels/{}/messages", thread["id"].as_str().unwrap());
+        mock.requests()
+            .iter()
+            .any(|req| req.url.starts_with(path.as_str()))
+    };
+    assert!(!fetched_messages_of(&stale));
+    assert!(!fetched_messages_of(&future));
+}
+
+#[test]
+fn test_forum_posts_count_with_their_tags() {
+    let mock = MockDiscord::start();
+    let mut forum = mock_discord::channel("forum", 15);
+    forum["available_tags"] = json!([
+        { "id": "t1", "name": "Shitpost", "moderated": false, "emoji_id": null, "emoji_name": null },
+        { "id": "t2", "name": "OC", "moderated": false, "emoji_id": null, "emoji_name": null },
+    ]);
+    mock.add_channel(forum);
+    let posted = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let old = Utc.with_ymd_and_hms(2024, 2, 10, 12, 0, 0).unwrap();
+    let post = mock_discord::thread("forum", posted, None, vec!["t2"]);
+    let old_post = mock_discord::thread("forum", old, Some(posted), vec!["t1"]);
+    mock.add_channel(post.clone());
+    mock.add_channel(old_post.clone());
+    let post_id = post["id"].as_str().unwrap();
+    let old_post_id = old_post["id"].as_str().unwrap();
+    mock.add_messages(
+        post_id,
+        vec![
+            mock_discord::message(post_id, posted, 0, "7", 5),
+            mock_discord::message(post_id, posted + Duration::hours(1), 0, "8", 9),
+        ],
+    );
+    mock.add_messages(
+        old_post_id,
+        vec![mock_discord::message(old_post_id, old, 0, "7", 5)],
+    );
+    let mut config = mock.config();
+    config.in_channel_ids = vec!["forum".to_string()];
+    config.include_threads = true;
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    let result = message_service
+        .get_memes_for_competition(&Competition::from_config(&config), &month)
+        .expect("???");
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, post_id);
+    assert_eq!(result[0].source_channel_id(), "forum");
+    assert_eq!(result[0].categories, vec!["OC".to_string()]);
+    assert!(!mock
+        .requests()
+        .iter()
+        .any(|req| req.url.starts_with("/channels/forum/messages")));
+}
+
+fn tenor_embed() -> serde_json::Value {
+    json!({
+        "type": "gifv",
+        "url": "https://tenor.com/view/cat-12345",
+        "provider": { "name": "Tenor", "url": "https://tenor.co" },
+        "thumbnail": {
+            "url": "https://media.tenor.com/cat.png",
+            "proxy_url": "https://images-ext-1.discordapp.net/external/cat.png",
+            "width": 498,
+            "height": 280,
+        },
+        "video": {
+            "url": "https://media.tenor.com/cat.mp4",
+            "proxy_url": "https://images-ext-1.discordapp.net/external/cat.mp4",
+            "width": 498,
+            "height": 280,
+        },
+    })
+}
+
+#[test]
+fn test_meme_media_resolves_link_embeds() {
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let parse = |value: serde_json::Value| -> Message { serde_json::from_value(value).unwrap() };
+    let link = |embed: serde_json::Value| {
+        parse(mock_discord::link_message(
+            "in",
+            timestamp,
+            0,
+            "1",
+            1,
+            "https://example.com",
+            embed,
+        ))
+    };
+
+    let upload =
+        meme_media(&parse(mock_discord::message("in", timestamp, 0, "1", 1))).expect("upload");
+    assert_eq!(upload.kind, MediaKind::Image);
+    assert_eq!(upload.preview_url.as_deref(), Some(upload.url.as_str()));
+
+    let gif = meme_media(&link(tenor_embed())).expect("gifv");
+    assert_eq!(gif.kind, MediaKind::Video);
+    assert_eq!(gif.url, "https://media.tenor.com/cat.mp4");
+    assert_eq!(
+        gif.preview_url.as_deref(),
+        Some("https://media.tenor.com/cat.png")
+    );
+
+    let imgur = meme_media(&link(json!({
+        "type": "image",
+        "url": "https://i.imgur.com/abc.jpg",
+        "thumbnail": { "url": "https://i.imgur.com/abc.jpg", "width": 640, "height": 480 },
+    })))
+    .expect("image");
+    assert_eq!(imgur.kind, MediaKind::Image);
+    assert_eq!(imgur.url, "https://i.imgur.com/abc.jpg");
+
+    let tweet = link(json!({
+        "type": "rich",
+        "url": "https://twitter.com/someone/status/1",
+        "description": "lol",
+        "image": { "url": "https://pbs.twimg.com/media/meme.jpg", "width": 1200, "height": 675 },
+    }));
+    let tweet = meme_media(&tweet).expect("tweet");
+    assert_eq!(tweet.kind, MediaKind::Image);
+    assert_eq!(tweet.url, "https://pbs.twimg.com/media/meme.jpg");
+
+    let reddit = meme_media(&link(json!({
+        "type": "rich",
+        "url": "https://www.reddit.com/r/memes/comments/abc/title/",
+        "provider": { "name": "reddit" },
+        "image": { "url": "https://i.redd.it/meme.png", "width": 800, "height": 800 },
+    })))
+    .expect("reddit");
+    assert_eq!(reddit.url, "https://i.redd.it/meme.png");
+
+    let blog = link(json!({
+        "type": "rich",
+        "url": "https://example.com/blog",
+        "image": { "url": "https://example.com/header.jpg", "width": 1200, "height": 630 },
+    }));
+    assert!(meme_media(&blog).is_none());
+
+    let article = link(json!({
+        "type": "article",
+        "url": "https://example.com/news",
+        "title": "No pictures here",
+    }));
+    assert!(meme_media(&article).is_none());
+}
+
+#[test]
+fn test_link_previews_are_not_ranked() {
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let preview: Message = serde_json::from_value(mock_discord::link_message(
+        "in",
+        timestamp,
+        0,
+        "1",
+        5,
+        "https://example.com/news",
+        json!({
+            "type": "article",
+            "url": "https://example.com/news",
+            "title": "Breaking news",
+            "thumbnail": { "url": "https://example.com/teaser.jpg", "width": 400, "height": 300 },
+        }),
+    ))
+    .unwrap();
+
+    assert!(sort_messages_by_upvote(vec![&preview], "👍").is_empty());
+}
+
+#[test]
+fn test_announce_ranks_link_memes() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    mock.add_messages(
+        "in",
+        vec![
+            mock_discord::message("in", timestamp, 0, "1", 2),
+            mock_discord::link_message(
+                "in",
+                timestamp,
+                1,
+                "2",
+                5,
+                "https://tenor.com/view/cat-12345",
+                tenor_embed(),
+            ),
+        ],
+    );
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&mock.config()),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let posted = mock.posted("out");
+    let embeds = posted[0]["embeds"].clone();
+    assert_eq!(embeds.as_array().unwrap().len(), 2);
+    assert_eq!(
+        embeds[0]["thumbnail"]["url"],
+        json!("https://media.tenor.com/cat.png")
+    );
+    assert!(embeds[0]["description"].as_str().unwrap().contains("<@2>"));
+}
+
+/// A message whose only attachment is `filename` of `content_type` with the given dimensions.
+fn upload(filename: &str, content_type: &str, size: Option<(i32, i32)>) -> Message {
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let mut msg = mock_discord::message("in", timestamp, 0, "1", 1);
+    let attachment = &mut msg["attachments"][0];
+    attachment["filename"] = json!(filename);
+    attachment["url"] = json!(format!(
+        "https://cdn.discordapp.com/attachments/in/1/{filename}"
+    ));
+    attachment["content_type"] = json!(content_type);
+    attachment["width"] = json!(size.map(|(width, _)| width));
+    attachment["height"] = json!(size.map(|(_, height)| height));
+    serde_json::from_value(msg).unwrap()
+}
+
+#[test]
+fn test_winner_embeds_follow_attachment_type() {
+    let embed_for = |msg: &Message| {
+        let media = meme_media(msg).expect("media");
+        serde_json::to_value(Embed::with_media("1.", "", &media)).unwrap()
+    };
+
+    let png = embed_for(&upload("meme.png", "image/png", Some((640, 480))));
+    assert!(png["image"]["url"].as_str().unwrap().ends_with("meme.png"));
+    assert_eq!(png["image"]["width"], json!(640));
+    assert!(png["thumbnail"].is_null());
+
+    let gif = upload("meme.gif", "image/gif", Some((320, 240)));
+    assert_eq!(meme_media(&gif).unwrap().kind, MediaKind::Gif);
+    assert!(embed_for(&gif)["image"]["url"]
+        .as_str()
+        .unwrap()
+        .ends_with("meme.gif"));
+
+    let mp4 = upload("meme.mp4", "video/mp4", Some((1280, 720)));
+    assert_eq!(meme_media(&mp4).unwrap().kind, MediaKind::Video);
+    let mp4 = embed_for(&mp4);
+    assert!(mp4["image"].is_null());
+    assert!(mp4["url"].as_str().unwrap().ends_with("meme.mp4"));
+
+    let heic = upload("meme.heic", "image/heic", None);
+    assert!(!meme_media(&heic).unwrap().is_embeddable());
+    assert!(embed_for(&heic)["image"].is_null());
+}
+
+#[test]
+fn test_announce_links_video_winners() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let mut video = mock_discord::message("in", timestamp, 0, "1", 3);
+    video["attachments"][0]["url"] = json!("https://cdn.discordapp.com/attachments/in/1/meme.mp4");
+    video["attachments"][0]["content_type"] = json!("video/mp4");
+    mock.add_messages(
+        "in",
+        vec![video, mock_discord::message("in", timestamp, 1, "2", 2)],
+    );
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&mock.config()),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let posted = mock.posted("out");
+    let content = posted[0]["content"].as_str().unwrap();
+    assert!(content.ends_with("\nhttps://cdn.discordapp.com/attachments/in/1/meme.mp4"));
+    assert_eq!(content.matches("https://").count(), 1);
+}
+
+#[test]
+fn test_announce_reuploads_winners_within_budget() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let mut small = mock_discord::message("in", timestamp, 0, "1", 3);
+    small["attachments"][0]["url"] =
+        json!(mock.add_file("/attachments/in/1/meme.png", vec![7; 2048]));
+    small["attachments"][0]["size"] = json!(2048);
+    let mut large = mock_discord::message("in", timestamp, 1, "2", 2);
+    large["attachments"][0]["size"] = json!(64 * 1024 * 1024);
+    mock.add_messages("in", vec![small, large]);
+    let mut config = mock.config();
+    config.reupload_winners = true;
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let post = mock
+        .requests()
+        .into_iter()
+        .find(|req| req.method == "POST")
+        .expect("announcement");
+    assert_eq!(post.files, vec![("0_meme.png".to_string(), 2048)]);
+    let body: serde_json::Value = serde_json::from_str(post.body.as_str()).unwrap();
+    assert_eq!(
+        body["attachments"],
+        json!([{ "id": 0, "filename": "0_meme.png" }])
+    );
+    assert_eq!(
+        body["embeds"][0]["image"]["url"],
+        json!("attachment://0_meme.png")
+    );
+    assert!(body["embeds"][1]["image"]["url"]
+        .as_str()
+        .unwrap()
+        .starts_with("https://cdn.discordapp.com/"));
+}
+
+#[test]
+fn test_announce_archives_winners() {
+    let mock = MockDiscord::start();
+    mock.add_channel(mock_discord::channel("in", 0));
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let data = vec![42; 512];
+    let mut msg = mock_discord::message("in", timestamp, 0, "1", 3);
+    msg["attachments"][0]["url"] = json!(mock.add_file("/attachments/in/1/meme.png", data.clone()));
+    let msg_id = msg["id"].as_str().unwrap().to_string();
+    mock.add_messages("in", vec![msg]);
+    let dir = std::env::temp_dir().join(format!("fnuef-archive-{}", std::process::id()));
+    let mut config = mock.config();
+    config.archive_dir = Some(dir.to_string_lossy().to_string());
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let month_dir = dir.join("memes").join("2024").join("03");
+    assert_eq!(std::fs::read(month_dir.join("01_meme.png")).unwrap(), data);
+    let entry: ArchiveEntry =
+        serde_json::from_slice(&std::fs::read(month_dir.join("01_meme.png.json")).unwrap())
+            .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(entry.month, "2024-03");
+    assert_eq!(entry.score, 3);
+    assert_eq!(
+        entry.message_link,
+        format!("https://discord.com/channels/guild/in/{msg_id}")
+    );
+    assert_eq!(entry.sha256, sha256_hex(&data));
+}
+
+#[test]
+fn test_archive_reuses_uploads_and_waits_for_the_announcement() {
+    let mock = MockDiscord::start();
+    mock.add_channel(mock_discord::channel("in", 0));
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let mut msg = mock_discord::message("in", timestamp, 0, "1", 3);
+    msg["attachments"][0]["url"] =
+        json!(mock.add_file("/attachments/in/1/meme.png", vec![42; 512]));
+    mock.add_messages("in", vec![msg]);
+    let dir = std::env::temp_dir().join(format!("fnuef-archive-send-{}", std::process::id()));
+    let mut config = mock.config();
+    config.archive_dir = Some(dir.to_string_lossy().to_string());
+    config.reupload_winners = true;
+    let message_service = MessageService::new(MessageGetter::from_config(&config));
+    let month = last_month_date(&fixed_clock()).expect("???");
+    let mut rejected = Competition::from_config(&config);
+    rejected.overflow = Overflow::Reject;
+    rejected.templates.intro = "x".repeat(2001).parse().unwrap();
+
+    let result = announce(&message_service, &rejected, &month, &fixed_clock());
+
+    assert!(result.is_err());
+    assert!(!dir.exists());
+
+    announce(
+        &message_service,
+        &Competition::from_config(&config),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let month_dir = dir.join("memes").join("2024").join("03");
+    assert!(month_dir.join("01_meme.png").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+    let downloads = mock
+        .requests()
+        .into_iter()
+        .filter(|req| req.url == "/attachments/in/1/meme.png")
+        .count();
+    // One download per announcement, the archive takes the uploaded bytes.
+    assert_eq!(downloads, 2);
+}
+
+#[test]
+fn test_embed_builder_serializes_spec_fields() {
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let embed = EmbedBuilder::new()
+        .title("1.")
+        .url("https://discord.com/channels/guild/in/1")
+        .timestamp(timestamp)
+        .color(0xFFD700)
+        .author(
+            "Meme Lord",
+            None,
+            Some("https://cdn.discordapp.com/avatars/1/a.png"),
+        )
+        .footer(
+            "memes",
+            Some("https://cdn.discordapp.com/icons/guild/i.png"),
+        )
+        .field("Channel", "<#in>", true)
+        .thumbnail("https://cdn.discordapp.com/t.png")
+        .image("attachment://meme.png")
+        .build();
+
+    let json = serde_json::to_value(&embed).unwrap();
+
+    assert_eq!(
+        json["author"],
+        json!({ "name": "Meme Lord", "icon_url": "https://cdn.discordapp.com/avatars/1/a.png" })
+    );
+    assert_eq!(
+        json["footer"]["icon_url"],
+        json!("https://cdn.discordapp.com/icons/guild/i.png")
+    );
+    assert_eq!(
+        json["thumbnail"],
+        json!({ "url": "https://cdn.discordapp.com/t.png" })
+    );
+    assert_eq!(json["timestamp"], json!("2024-03-10T12:00:00Z"));
+    assert_eq!(json["color"], json!(0xFFD700));
+    assert_eq!(json["fields"][0]["name"], json!("Channel"));
+    assert!(json.get("video").is_none() && json.get("provider").is_none());
+}
+
+#[test]
+fn test_link_embed_keeps_proxy_urls_and_provider() {
+    let embed: Embed = serde_json::from_value(tenor_embed()).unwrap();
+
+    assert_eq!(embed.provider.unwrap().name.as_deref(), Some("Tenor"));
+    assert_eq!(
+        embed.thumbnail.unwrap().proxy_url.as_deref(),
+        Some("https://images-ext-1.discordapp.net/external/cat.png")
+    );
+}
+
+fn winner_embeds(count: usize, description: &str) -> MessageBodyBuilder {
+    (0..count).fold(MessageBodyBuilder::new("Winners:"), |builder, idx| {
+        let file = FileUpload {
+            filename: format!("{idx}.png"),
+            content_type: "image/png".to_string(),
+            data: vec![0; 8],
+        };
+        let embed = EmbedBuilder::new()
+            .title(format!("{}.", idx + 1).as_str())
+            .description(description)
+            .image(file.attachment_url().as_str())
+            .build();
+        builder.add_embed(embed).add_file(file)
+    })
+}
+
+#[test]
+fn test_message_limits_reject_with_descriptive_error() {
+    let too_many = winner_embeds(11, "").build();
+    let long_title = MessageBodyBuilder::new("")
+        .add_embed(EmbedBuilder::new().title(&"x".repeat(257)).build())
+        .build();
+    let too_long_together = winner_embeds(2, &"x".repeat(3500)).build();
+
+    assert!(validate(&winner_embeds(10, "").build()).is_ok());
+    let errors: Vec<String> = [too_many, long_title, too_long_together]
+        .iter()
+        .map(|body| validate(body).unwrap_err().to_string())
+        .collect();
+    assert!(
+        errors[0].contains("at most 10 embeds, not 11"),
+        "{}",
+        errors[0]
+    );
+    assert!(
+        errors[1].contains("Title has 257 characters"),
+        "{}",
+        errors[1]
+    );
+    assert!(
+        errors[2].contains("more than the 6000 allowed"),
+        "{}",
+        errors[2]
+    );
+    assert!(matches!(
+        winner_embeds(11, "").build_messages(Overflow::Reject),
+        Err(Error::MessageLimit(_))
+    ));
+}
+
+#[test]
+fn test_message_limits_split_long_announcement() {
+    let content = vec!["x".repeat(1500); 2].join("\n");
+    let messages = winner_embeds(12, "")
+        .add_line(content.as_str())
+        .build_messages(Overflow::Split)
+        .unwrap();
+
+    assert_eq!(messages.len(), 2);
+    let (first, first_files) = &messages[0];
+    let (second, second_files) = &messages[1];
+    assert_eq!(first.embeds.as_ref().unwrap().len(), 10);
+    assert_eq!(second.embeds.as_ref().unwrap().len(), 2);
+    assert_eq!(first_files.len(), 10);
+    assert_eq!(second_files[0].filename, "10.png");
+    assert!(first.content.starts_with("Winners:"));
+    assert_eq!(second.content, "x".repeat(1500));
+    assert!(messages.iter().all(|(body, _)| validate(body).is_ok()));
+}
+
+#[test]
+fn test_message_limits_split_keeps_unshown_files_with_their_embeds() {
+    let videos = (0..12).fold(MessageBodyBuilder::new("Winners:"), |builder, idx| {
+        let file = FileUpload {
+            filename: format!("{idx}.mp4"),
+            content_type: "video/mp4".to_string(),
+            data: vec![0; 8],
+        };
+        let embed = EmbedBuilder::new()
+            .title(format!("{}.", idx + 1).as_str())
+            .url(format!("https://cdn.example/{idx}.mp4").as_str())
+            .build();
+        builder.add_embed(embed).add_file(file)
+    });
+
+    let messages = videos.build_messages(Overflow::Split).unwrap();
+
+    let names = |files: &Vec<FileUpload>| -> Vec<String> {
+        files.iter().map(|file| file.filename.clone()).collect()
+    };
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].0.embeds.as_ref().unwrap().len(), 10);
+    assert_eq!(
+        names(&messages[0].1),
+        (0..10).map(|idx| format!("{idx}.mp4")).collect::<Vec<_>>()
+    );
+    assert_eq!(names(&messages[1].1), ["10.mp4", "11.mp4"]);
+}
+
+#[test]
+fn test_message_limits_truncate_texts_and_drop_embeds() {
+    let messages = winner_embeds(12, &"x".repeat(5000))
+        .build_messages(Overflow::Truncate)
+        .unwrap();
+
+    assert_eq!(messages.len(), 1);
+    let (body, files) = &messages[0];
+    let embeds = body.embeds.as_ref().unwrap();
+    assert_eq!(embeds.len(), 1);
+    assert_eq!(embeds[0].description.chars().count(), 4096);
+    assert!(embeds[0].description.ends_with('…'));
+    assert_eq!(files.len(), 1);
+    assert!(validate(body).is_ok());
+}
+
+#[test]
+fn test_download_stops_at_max_bytes() {
+    let mock = MockDiscord::start();
+    let url = mock.add_file("/attachments/in/1/meme.png", vec![7; 2048]);
+    let getter = MessageGetter::from_config(&mock.config());
+
+    let capped = ReqwestTransport::new().get_capped(url.as_str(), vec![], 1024);
+    let result = getter.download(url.as_str(), 1024);
+
+    assert!(matches!(
+        capped,
+        Err(Error::TooLarge {
+            max_bytes: 1024,
+            ..
+        })
+    ));
+    assert!(matches!(
+        result,
+        Err(Error::TooLarge {
+            max_bytes: 1024,
+            ..
+        })
+    ));
+    assert_eq!(
+        getter.download(url.as_str(), 2048).expect("???").len(),
+        2048
+    );
+}
+
+#[test]
+fn test_multipart_parts_carry_payload_json_and_files() {
+    let file = |idx: usize| FileUpload {
+        filename: format!("{idx}.png"),
+        content_type: "image/png".to_string(),
+        data: vec![1, 2, 3],
+    };
+
+    let parts = multipart_parts(
+        MessageBodyBuilder::new("hi").build(),
+        vec![file(0), file(1)],
+    )
+    .expect("parts");
+
+    let names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+    assert_eq!(names, vec!["payload_json", "files[0]", "files[1]"]);
+    let payload: serde_json::Value = serde_json::from_slice(&parts[0].data).unwrap();
+    assert_eq!(payload["content"], json!("hi"));
+    assert_eq!(payload["attachments"][1]["filename"], json!("1.png"));
+    assert_eq!(parts[2].filename.as_deref(), Some("1.png"));
+
+    let too_many = (0..11).map(file).collect();
+    assert!(multipart_parts(MessageBodyBuilder::new("hi").build(), too_many).is_err());
+}
+
+#[test]
+fn test_cdn_expiry_parses_hex_timestamp() {
+    let url = "https://cdn.discordapp.com/attachments/1/2/meme.png?ex=65fa2700&is=65f8d580&hm=abc&";
+
+    assert_eq!(
+        cdn_expiry(url),
+        Some(Utc.with_ymd_and_hms(2024, 3, 20, 0, 0, 0).unwrap())
+    );
+    assert!(is_expired(url, &fixed_clock()));
+    assert!(!is_expired(
+        url,
+        &FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 19, 0, 0, 0).unwrap())
+    ));
+    assert_eq!(cdn_expiry("https://media.tenor.com/cat.png"), None);
+    assert!(!is_expired(
+        "https://media.tenor.com/cat.png",
+        &fixed_clock()
+    ));
+}
+
+#[test]
+fn test_announce_refreshes_expired_links() {
+    let mock = MockDiscord::start();
+    let timestamp = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+    let expired =
+        "https://cdn.discordapp.com/attachments/in/1/old.png?ex=65fa2700&is=65f8d580&hm=abc&";
+    let fresh =
+        "https://cdn.discordapp.com/attachments/in/2/new.png?ex=f6663900&is=f6663900&hm=abc&";
+    let mut old = mock_discord::message("in", timestamp, 0, "1", 3);
+    old["attachments"][0]["url"] = json!(expired);
+    let mut new = mock_discord::message("in", timestamp, 1, "2", 2);
+    new["attachments"][0]["url"] = json!(fresh);
+    mock.add_messages("in", vec![old, new]);
+    let message_service = MessageService::new(MessageGetter::from_config(&mock.config()));
+    let month = last_month_date(&fixed_clock()).expect("???");
+
+    announce(
+        &message_service,
+        &Competition::from_config(&mock.config()),
+        &month,
+        &fixed_clock(),
+    )
+    .expect("???");
+
+    let refresh: Vec<_> = mock
+        .requests()
+        .into_iter()
+        .filter(|req| req.url == "/attachments/refresh-urls")
+        .collect();
+    assert_eq!(refresh.len(), 1);
+    let requested: serde_json::Value = serde_json::from_str(refresh[0].body.as_str()).unwrap();
+    assert_eq!(requested["attachment_urls"], json!([expired]));
+    let embeds = mock.posted("out")[0]["embeds"].clone();
+    assert_eq!(
+        embeds[0]["image"]["url"],
+        json!(mock_discord::refreshed_url(expired))
+    );
+    assert_eq!(embeds[1]["image"]["url"], json!(fresh));
 }
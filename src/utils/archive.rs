@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Utc};
+use log::debug;
+use sha2::{Digest, Sha256};
+
+use crate::discord_api::uploads::{DownloadFiles, FileUpload};
+use crate::models::archive::{ArchiveEntry, ArchivedAuthor};
+use crate::models::discord::Message;
+use crate::prelude::*;
+use crate::utils::media::{file_name, Media};
+
+/// A directory keeping the winning memes of every competition, organized as
+/// `<competition>/<year>/<month>/`.
+///
+/// Each meme file gets a `<file>.json` sidecar (see `ArchiveEntry`), so the history survives
+/// even if the messages are deleted on Discord.
+pub struct Archive {
+    dir: PathBuf,
+    competition: String,
+    /// Files larger than this are not downloaded
+    max_file_bytes: u64,
+}
+
+/// A winning meme to archive.
+pub struct ArchivedWinner<'a> {
+    /// Position among the winners of the month, starting at 1, prefixes the file names
+    pub rank: usize,
+    pub place: &'a str,
+    pub msg: &'a Message,
+    pub media: Media,
+    pub score: i32,
+    pub message_link: String,
+    /// The meme as it was attached to the announcement, saves downloading it again
+    pub upload: Option<FileUpload>,
+}
+
+impl Archive {
+    pub fn new(dir: impl Into<PathBuf>, competition: &str, max_file_bytes: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            competition: competition.to_string(),
+            max_file_bytes,
+        }
+    }
+
+    /// The directory the winners of `month` are stored in.
+    pub fn month_dir(&self, month: &DateTime<Utc>) -> PathBuf {
+        self.dir
+            .join(file_name(self.competition.as_str()))
+            .join(format!("{:04}", month.year()))
+            .join(format!("{:02}", month.month()))
+    }
+
+    /// Downloads all attachments of `winner`, or its linked meme if it has none, and stores
+    /// them with their sidecars. The meme already attached to the announcement is taken from
+    /// `winner.upload`. Returns the paths of the stored files.
+    pub fn store_winner(
+        &self,
+        downloader: &impl DownloadFiles,
+        month: &DateTime<Utc>,
+        winner: &ArchivedWinner,
+    ) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<(String, String)> = winner
+            .msg
+            .attachments
+            .iter()
+            .map(|attachment| (attachment.url.clone(), attachment.filename.clone()))
+            .collect();
+        if files.is_empty() {
+            let url = winner.media.url.clone();
+            let filename = file_name(url.as_str());
+            files.push((url, filename));
+        }
+
+        let dir = self.month_dir(month);
+        fs::create_dir_all(&dir)?;
+        let mut stored = Vec::new();
+        for (idx, (url, original_filename)) in files.into_iter().enumerate() {
+            let downloaded;
+            let data: &[u8] = match &winner.upload {
+                Some(upload) if url == winner.media.url => &upload.data,
+                _ => {
+                    downloaded = downloader.download(url.as_str(), self.max_file_bytes)?;
+                    &downloaded
+                }
+            };
+            let file = match idx {
+                0 => format!("{:02}_{}", winner.rank, file_name(&original_filename)),
+                _ => format!("{:02}_{idx}_{}", winner.rank, file_name(&original_filename)),
+            };
+            let entry = ArchiveEntry {
+                competition: self.competition.clone(),
+                month: format!("{:04}-{:02}", month.year(), month.month()),
+                place: winner.place.to_string(),
+                author: ArchivedAuthor {
+                    id: winner.msg.author.id.clone(),
+                    username: winner.msg.author.username.clone(),
+                    global_name: winner.msg.author.global_name.clone(),
+                },
+                score: winner.score,
+                message_link: winner.message_link.clone(),
+                original_filename,
+                file: file.clone(),
+                sha256: sha256_hex(data),
+            };
+            stored.push(write_entry(&dir, &entry, data)?);
+        }
+        Ok(stored)
+    }
+}
+
+fn write_entry(dir: &Path, entry: &ArchiveEntry, data: &[u8]) -> Result<PathBuf> {
+    let path = dir.join(entry.file.as_str());
+    fs::write(&path, data)?;
+    fs::write(
+        dir.join(format!("{}.json", entry.file)),
+        serde_json::to_string_pretty(entry)?,
+    )?;
+    debug!("Archived {:?}", path);
+    Ok(path)
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
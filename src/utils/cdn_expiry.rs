@@ -0,0 +1,24 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::utils::clock::Clock;
+
+/// Returns when the signed CDN link `url` stops working.
+///
+/// Discord signs attachment links with `ex` (expiry), `is` (issued) and `hm` (signature)
+/// query parameters, the timestamps being unix seconds in hex. Links without `ex` never expire.
+pub fn cdn_expiry(url: &str) -> Option<DateTime<Utc>> {
+    let (_, query) = url.split_once('?')?;
+    let query = query.split('#').next().unwrap_or_default();
+    let expiry = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "ex")?
+        .1;
+    let seconds = i64::from_str_radix(expiry, 16).ok()?;
+    Utc.timestamp_opt(seconds, 0).single()
+}
+
+/// Whether the signed CDN link `url` has expired, or will within a minute.
+pub fn is_expired(url: &str, clock: &impl Clock) -> bool {
+    cdn_expiry(url).is_some_and(|expiry| expiry <= clock.now() + chrono::Duration::minutes(1))
+}
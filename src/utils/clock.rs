@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+
+/// Source of the current time.
+///
+/// What depends on "now", like picking the month to announce or telling whether CDN links
+/// expired, takes a `Clock` instead of calling `Utc::now()` directly, so tests can run against a
+/// fixed point in time.
+pub trait Clock {
+    /// Returns the current time in UTC.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock, backed by `Utc::now()`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that always returns the same instant.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    now: DateTime<Utc>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+}
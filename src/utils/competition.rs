@@ -0,0 +1,92 @@
+use serde::Deserialize;
+
+use crate::utils::config::Config;
+use crate::utils::i18n::Locale;
+use crate::utils::message_limits::Overflow;
+use crate::utils::template::AnnouncementTemplates;
+
+/// A monthly meme competition: where memes are collected and how they are ranked.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Competition {
+    pub name: String,
+    /// Channels whose messages take part, fetched concurrently
+    pub in_channel_ids: Vec<String>,
+    /// The emoji that counts as the upvote or the id of the custom reaction
+    pub upvote_emoji: String,
+    /// Also award the best meme of every input channel besides the overall top three
+    pub channel_awards: bool,
+    /// Also collect memes posted in threads of the input channels, for forum channels the posts
+    pub include_threads: bool,
+    /// Attach the winning memes to the announcement instead of linking them
+    pub reupload_winners: bool,
+    /// Upload budget of one announcement in bytes, memes that do not fit are linked
+    pub upload_max_bytes: u64,
+    /// Keep the winners in this directory, see `Archive`
+    pub archive_dir: Option<String>,
+    /// Largest file in bytes downloaded into the archive
+    pub archive_max_bytes: u64,
+    /// What to do with announcements exceeding Discord's limits
+    pub overflow: Overflow,
+    /// Mention the winners in the announcement's content, so they get notified
+    pub ping_winners: bool,
+    /// Role mentioned in the announcement's content, so its members get notified
+    pub notify_role_id: Option<String>,
+    /// Only let the winners and `notify_role_id` be notified, no matter what the texts mention
+    pub suppress_mentions: bool,
+    /// React to the winning messages with their medal
+    pub react_to_winners: bool,
+    /// Reply to the winning messages with their place, where they were posted
+    pub reply_to_winners: bool,
+    /// Pin each month's winner in the channel it was posted in
+    pub pin_winners: bool,
+    /// Winners the bot pinned are unpinned, oldest first, while a channel has this many pins
+    pub pin_limit: usize,
+    /// Role passed on to each month's winner, taken from the previous one
+    pub winner_role_id: Option<String>,
+    /// Where the bot keeps what it pinned and who holds the winner role, see `StateFile`
+    pub state_file: String,
+    /// Crosspost the announcement if it is posted in an announcement channel, so servers
+    /// following it receive it too
+    pub crosspost: bool,
+    /// Channel whose topic names the current winner, e.g. the meme channel
+    pub topic_channel_id: Option<String>,
+    /// Language of everything the bot posts
+    pub locale: Locale,
+    /// Texts of the announcement, by default those of `locale`
+    pub templates: AnnouncementTemplates,
+}
+
+impl Competition {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            name: config.competition_name.clone(),
+            in_channel_ids: config.in_channel_ids.clone(),
+            upvote_emoji: config.upvote_emoji.clone(),
+            channel_awards: config.channel_awards,
+            include_threads: config.include_threads,
+            reupload_winners: config.reupload_winners,
+            upload_max_bytes: config.upload_max_bytes,
+            archive_dir: config.archive_dir.clone(),
+            archive_max_bytes: config.archive_max_bytes,
+            overflow: config.overflow,
+            ping_winners: config.ping_winners,
+            notify_role_id: config.notify_role_id.clone(),
+            suppress_mentions: config.suppress_mentions,
+            react_to_winners: config.react_to_winners,
+            reply_to_winners: config.reply_to_winners,
+            pin_winners: config.pin_winners,
+            pin_limit: config.pin_limit,
+            winner_role_id: config.winner_role_id.clone(),
+            state_file: config.state_file.clone(),
+            crosspost: config.crosspost,
+            topic_channel_id: config.topic_channel_id.clone(),
+            locale: config.locale,
+            templates: config.templates.clone(),
+        }
+    }
+
+    /// Whether memes come from more than one channel, so winners need to say where from.
+    pub fn is_multi_channel(&self) -> bool {
+        self.in_channel_ids.len() > 1
+    }
+}
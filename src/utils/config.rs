@@ -2,28 +2,128 @@ use dotenv::dotenv;
 use lazy_static::lazy_static;
 use serde::Deserialize;
 use std::env;
+use std::str::FromStr;
+
+use crate::utils::i18n::Locale;
+use crate::utils::message_limits::Overflow;
+use crate::utils::template::AnnouncementTemplates;
 
 #[derive(Deserialize)]
 pub struct Config {
     pub token: String,
     pub client_id: String,
     pub base_url: String,
-    pub in_channel_id: String,
+    /// Name of the competition, used in archive paths
+    pub competition_name: String,
+    /// Channels memes are collected from, `IN_CHANNEL_IDS` is a comma separated list
+    pub in_channel_ids: Vec<String>,
     pub out_channel_id: String,
     pub upvote_emoji: String,
+    /// Also award the best meme of every input channel
+    pub channel_awards: bool,
+    /// Also collect memes from threads and forum posts of the input channels, costs extra
+    /// requests per channel and thread, see `MessageService::get_memes_for_competition`
+    pub include_threads: bool,
+    /// Upload the winning memes instead of linking them, CDN links expire
+    pub reupload_winners: bool,
+    /// How many bytes of files one announcement may upload
+    pub upload_max_bytes: u64,
+    /// Directory to keep the winning memes in, see `utils::archive`
+    pub archive_dir: Option<String>,
+    /// Largest file in bytes downloaded into the archive
+    pub archive_max_bytes: u64,
+    /// What to do with announcements exceeding Discord's limits
+    pub overflow: Overflow,
+    /// Mention the winners in the announcement's content, so they get notified
+    pub ping_winners: bool,
+    /// Role mentioned in the announcement's content, so its members get notified
+    pub notify_role_id: Option<String>,
+    /// Only let the winners and `notify_role_id` be notified, no matter what the texts mention
+    pub suppress_mentions: bool,
+    /// React to the winning messages with their medal
+    pub react_to_winners: bool,
+    /// Reply to the winning messages with their place, where they were posted
+    pub reply_to_winners: bool,
+    /// Pin each month's winner in the channel it was posted in
+    pub pin_winners: bool,
+    /// Winners the bot pinned are unpinned, oldest first, while a channel has this many pins
+    pub pin_limit: usize,
+    /// Role passed on to each month's winner, taken from the previous one
+    pub winner_role_id: Option<String>,
+    /// Where the bot keeps what it pinned and who holds the winner role, see `StateFile`
+    pub state_file: String,
+    /// Crosspost the announcement if `out_channel_id` is an announcement channel
+    pub crosspost: bool,
+    /// Channel whose topic names the current winner, e.g. the meme channel
+    pub topic_channel_id: Option<String>,
+    /// Language of everything the bot posts, e.g. `en` or `de`
+    pub locale: Locale,
+    /// Texts of the announcement, `TEMPLATE_INTRO`, `TEMPLATE_TITLE`, ... override the defaults
+    pub templates: AnnouncementTemplates,
+    /// Directory to record sanitized Discord responses into, see `discord_api::fixtures`
+    pub record_fixtures: Option<String>,
+    /// How often a Discord request is attempted before giving up
+    pub retry_max_attempts: u32,
+    /// Delay before the first retry, doubled for every further attempt
+    pub retry_base_delay_ms: u64,
+    /// Upper bound for the delay between two attempts, longer rate limits are not waited out
+    pub retry_max_delay_ms: u64,
 }
 
 impl Config {
     fn new() -> Self {
         dotenv().expect("Could not find .env file!");
+        let locale = env_or("LOCALE", Locale::En);
+        let templates = AnnouncementTemplates::for_locale(locale);
+        let templates = AnnouncementTemplates {
+            intro: env_or("TEMPLATE_INTRO", templates.intro),
+            title: env_or("TEMPLATE_TITLE", templates.title),
+            description: env_or("TEMPLATE_DESCRIPTION", templates.description),
+            footer: env_or("TEMPLATE_FOOTER", templates.footer),
+            topic: env_or("TEMPLATE_TOPIC", templates.topic),
+        };
+        if let Err(err) = templates.validate() {
+            panic!("Invalid template: {err}");
+        }
 
         Self {
             token: env::var("TOKEN").expect("TOKEN must be set"),
             client_id: env::var("CLIENT_ID").expect("CLIENT_ID must be set"),
             base_url: env::var("BASE_URL").expect("BASE_URL must be set"),
-            in_channel_id: env::var("IN_CHANNEL_ID").expect("IN_CHANNEL_ID must be set"),
+            competition_name: env_or("COMPETITION_NAME", "memes".to_string()),
+            in_channel_ids: env::var("IN_CHANNEL_IDS")
+                .or_else(|_| env::var("IN_CHANNEL_ID"))
+                .expect("IN_CHANNEL_IDS must be set")
+                .split(',')
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect(),
             out_channel_id: env::var("OUT_CHANNEL_ID").expect("OUT_CHANNEL_ID must be set"),
             upvote_emoji: env::var("UPVOTE_EMOJI").expect("UPVOTE_EMOJI must be set"),
+            channel_awards: env_or("CHANNEL_AWARDS", false),
+            include_threads: env_or("INCLUDE_THREADS", false),
+            reupload_winners: env_or("REUPLOAD_WINNERS", false),
+            upload_max_bytes: env_or("UPLOAD_MAX_BYTES", 10 * 1024 * 1024),
+            archive_dir: env::var("ARCHIVE_DIR").ok(),
+            archive_max_bytes: env_or("ARCHIVE_MAX_BYTES", 100 * 1024 * 1024),
+            overflow: env_or("MESSAGE_OVERFLOW", Overflow::Split),
+            ping_winners: env_or("PING_WINNERS", false),
+            notify_role_id: env::var("NOTIFY_ROLE_ID").ok(),
+            suppress_mentions: env_or("SUPPRESS_MENTIONS", true),
+            react_to_winners: env_or("REACT_TO_WINNERS", false),
+            reply_to_winners: env_or("REPLY_TO_WINNERS", false),
+            pin_winners: env_or("PIN_WINNERS", false),
+            pin_limit: env_or("PIN_LIMIT", 45),
+            winner_role_id: env::var("WINNER_ROLE_ID").ok(),
+            state_file: env_or("STATE_FILE", "fmr-state.json".to_string()),
+            crosspost: env_or("CROSSPOST", false),
+            topic_channel_id: env::var("TOPIC_CHANNEL_ID").ok(),
+            locale,
+            templates,
+            record_fixtures: env::var("RECORD_FIXTURES").ok(),
+            retry_max_attempts: env_or("RETRY_MAX_ATTEMPTS", 5),
+            retry_base_delay_ms: env_or("RETRY_BASE_DELAY_MS", 500),
+            retry_max_delay_ms: env_or("RETRY_MAX_DELAY_MS", 30_000),
         }
     }
 
@@ -34,3 +134,13 @@ impl Config {
         &INSTANCE
     }
 }
+
+/// Reads and parses the variable `key`, falling back to `default` if it is unset.
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    match env::var(key) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("{key} has an invalid value: {value}")),
+        Err(_) => default,
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::discord::{
+    Embed, EmbedAuthor, EmbedField, EmbedFooter, EmbedImage, EmbedThumbnail,
+};
+
+/// Builds the embeds a bot can send.
+///
+/// Videos and providers are left out, Discord only sets those on embeds it generates for links.
+#[derive(Default)]
+pub struct EmbedBuilder {
+    embed: Embed,
+}
+
+/// Continues building `embed`, e.g. one made by `Embed::with_media`.
+impl From<Embed> for EmbedBuilder {
+    fn from(embed: Embed) -> Self {
+        Self { embed }
+    }
+}
+
+impl EmbedBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.embed.title = title.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.embed.description = description.to_string();
+        self
+    }
+
+    /// The url the title links to.
+    pub fn url(mut self, url: &str) -> Self {
+        self.embed.url = Some(url.to_string());
+        self
+    }
+
+    /// Shown in the footer, each reader sees it in their own time zone.
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.embed.timestamp = Some(timestamp);
+        self
+    }
+
+    /// The color of the bar on the left, as `0xRRGGBB`.
+    pub fn color(mut self, color: u32) -> Self {
+        self.embed.color = Some(color);
+        self
+    }
+
+    /// Shows `name` above the title, linking to `url` and with `icon_url` as avatar.
+    pub fn author(mut self, name: &str, url: Option<&str>, icon_url: Option<&str>) -> Self {
+        self.embed.author = Some(EmbedAuthor {
+            name: name.to_string(),
+            url: url.map(str::to_string),
+            icon_url: icon_url.map(str::to_string),
+            proxy_icon_url: None,
+        });
+        self
+    }
+
+    pub fn footer(mut self, text: &str, icon_url: Option<&str>) -> Self {
+        self.embed.footer = Some(EmbedFooter {
+            text: text.to_string(),
+            icon_url: icon_url.map(str::to_string),
+            proxy_icon_url: None,
+        });
+        self
+    }
+
+    /// Appends a field, `inline` fields share a row with their neighbours.
+    pub fn field(mut self, name: &str, value: &str, inline: bool) -> Self {
+        self.embed
+            .fields
+            .get_or_insert_with(Vec::new)
+            .push(EmbedField {
+                title: name.to_string(),
+                value: value.to_string(),
+                inline,
+            });
+        self
+    }
+
+    /// The large image below the description, also `attachment://<filename>` for uploads.
+    pub fn image(self, url: &str) -> Self {
+        self.sized_image(url, None, None)
+    }
+
+    /// Like `image`, with the dimensions of the image if they are known.
+    pub fn sized_image(mut self, url: &str, width: Option<i32>, height: Option<i32>) -> Self {
+        self.embed.image = Some(EmbedImage {
+            url: url.to_string(),
+            proxy_url: None,
+            width,
+            height,
+        });
+        self
+    }
+
+    /// The small image in the top right corner.
+    pub fn thumbnail(mut self, url: &str) -> Self {
+        self.embed.thumbnail = Some(EmbedThumbnail {
+            url: url.to_string(),
+            proxy_url: None,
+            width: None,
+            height: None,
+        });
+        self
+    }
+
+    pub fn build(self) -> Embed {
+        self.embed
+    }
+}
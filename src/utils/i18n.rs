@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use log::warn;
+use serde::Deserialize;
+
+use crate::prelude::*;
+use crate::utils::template::Template;
+
+/// A language the bot speaks, each with a message catalog in `locales/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    En,
+    De,
+}
+
+impl Locale {
+    /// The locale whose catalog fills in missing messages of the others.
+    pub const FALLBACK: Locale = Locale::En;
+
+    /// The message `key` in this locale, the key itself if no catalog has it.
+    pub fn text(self, key: &str) -> String {
+        match CATALOGS[&self]
+            .get(key)
+            .or_else(|| CATALOGS[&Self::FALLBACK].get(key))
+        {
+            Some(text) => text.clone(),
+            None => {
+                warn!("Missing message {key}");
+                key.to_string()
+            }
+        }
+    }
+
+    /// The message `key` with its `{variables}` filled in from `vars`.
+    pub fn format(self, key: &str, vars: &HashMap<&str, String>) -> Result<String> {
+        self.template(key)?.render(vars)
+    }
+
+    /// The message `key` as template, e.g. as default for a competition's texts.
+    pub fn template(self, key: &str) -> Result<Template> {
+        self.text(key).parse()
+    }
+}
+
+impl FromStr for Locale {
+    type Err = Error;
+
+    /// Parses language tags like `de`, `de-DE` or `en_US`.
+    fn from_str(value: &str) -> Result<Self> {
+        let language = value.split(['-', '_']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Ok(Locale::En),
+            "de" => Ok(Locale::De),
+            _ => Err(Error::Generic(format!("Unsupported locale {value}"))),
+        }
+    }
+}
+
+lazy_static! {
+    static ref CATALOGS: HashMap<Locale, HashMap<String, String>> = HashMap::from([
+        (Locale::En, catalog(include_str!("../../locales/en.json"))),
+        (Locale::De, catalog(include_str!("../../locales/de.json"))),
+    ]);
+}
+
+fn catalog(json: &str) -> HashMap<String, String> {
+    serde_json::from_str(json).expect("Message catalogs are valid JSON")
+}
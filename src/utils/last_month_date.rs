@@ -1,10 +1,9 @@
-use chrono::{DateTime, Datelike, Months, Timelike, Utc};
+use chrono::{DateTime, Utc};
 
-pub fn last_month_date() -> Option<DateTime<Utc>> {
-    let res: DateTime<Utc> = Utc::now().checked_sub_months(Months::new(0))?;
-    res.with_day(1)?
-        .with_hour(0)?
-        .with_minute(0)?
-        .with_second(0)?;
-    return Some(res);
+use crate::utils::clock::Clock;
+use crate::utils::period::Period;
+
+/// Returns the first day of the month before `clock.now()` at midnight UTC.
+pub fn last_month_date(clock: &impl Clock) -> Option<DateTime<Utc>> {
+    Period::last_month(clock).ok().map(|period| period.start)
 }
@@ -0,0 +1,210 @@
+use crate::models::discord::{Attachment, Embed, Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    /// An animated image, embeds play these like images
+    Gif,
+    /// Embeds cannot play videos, they need to be linked or uploaded
+    Video,
+}
+
+/// The picture or clip a meme consists of, wherever it was posted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Media {
+    /// Where the meme itself lives
+    pub url: String,
+    /// A still image that can be shown in an embed, for images `url` itself
+    pub preview_url: Option<String>,
+    pub kind: MediaKind,
+    /// MIME type, if Discord knows it
+    pub content_type: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+}
+
+impl Media {
+    /// Whether an embed image can show the meme as it is.
+    ///
+    /// Discord only measures uploads it can render, so an upload without dimensions (HEIC, TIFF
+    /// and the like) is a file to link, not an image to embed.
+    pub fn is_embeddable(&self) -> bool {
+        let unrenderable_upload =
+            self.content_type.is_some() && (self.width.is_none() || self.height.is_none());
+        self.kind != MediaKind::Video && !unrenderable_upload
+    }
+}
+
+/// Resolves the meme in `msg`, `None` for messages without an image or video.
+///
+/// Uploaded files win over links. For links (Tenor, Imgur, Reddit, Twitter, ...) the embed
+/// Discord generated is used: its video, then its image, then its thumbnail.
+pub fn meme_media(msg: &Message) -> Option<Media> {
+    msg.attachments
+        .iter()
+        .find_map(attachment_media)
+        .or_else(|| msg.embeds.iter().find_map(embed_media))
+}
+
+fn attachment_media(attachment: &Attachment) -> Option<Media> {
+    let content_type = attachment.content_type.as_deref().unwrap_or_default();
+    let extension = file_extension(attachment.url.as_str());
+    let kind = if content_type == "image/gif" || (content_type.is_empty() && extension == "gif") {
+        MediaKind::Gif
+    } else if content_type.starts_with("video/") {
+        MediaKind::Video
+    } else if content_type.starts_with("image/") || is_image_extension(extension.as_str()) {
+        MediaKind::Image
+    } else {
+        return None;
+    };
+
+    Some(Media {
+        url: attachment.url.clone(),
+        preview_url: (kind != MediaKind::Video && attachment.width.is_some())
+            .then(|| attachment.url.clone()),
+        kind,
+        content_type: attachment.content_type.clone(),
+        width: attachment.width,
+        height: attachment.height,
+    })
+}
+
+fn embed_media(embed: &Embed) -> Option<Media> {
+    let image = embed
+        .image
+        .as_ref()
+        .map(|image| (image.url.clone(), image.width, image.height));
+    let thumbnail = embed
+        .thumbnail
+        .as_ref()
+        .map(|thumbnail| (thumbnail.url.clone(), thumbnail.width, thumbnail.height));
+    let still = image.or(thumbnail);
+    let video = embed.video.as_ref().and_then(|video| {
+        let url = video.url.clone()?;
+        Some((url, video.width, video.height))
+    });
+
+    match (embed._type.as_deref(), video, still) {
+        // Tenor and Imgur GIFs are served as silent looping videos.
+        (Some("gifv" | "video"), Some((url, width, height)), still) => Some(Media {
+            url,
+            preview_url: still.map(|(url, _, _)| url),
+            kind: MediaKind::Video,
+            content_type: None,
+            width,
+            height,
+        }),
+        // Bare image links carry the image as thumbnail.
+        (Some("image"), _, Some((still_url, width, height))) => {
+            let url = embed.url.clone().unwrap_or_else(|| still_url.clone());
+            Some(Media {
+                kind: image_kind(url.as_str()),
+                url,
+                preview_url: Some(still_url),
+                content_type: None,
+                width,
+                height,
+            })
+        }
+        // Videos without a playable url still show their preview.
+        (Some("gifv" | "video"), None, Some((url, width, height))) => Some(Media {
+            kind: image_kind(url.as_str()),
+            preview_url: Some(url.clone()),
+            url,
+            content_type: None,
+            width,
+            height,
+        }),
+        // Tweets and Reddit posts are rich embeds, their image is the meme.
+        (Some("rich"), _, _) if is_meme_post(embed) => {
+            let image = embed.image.as_ref()?;
+            Some(Media {
+                kind: image_kind(image.url.as_str()),
+                url: image.url.clone(),
+                preview_url: Some(image.url.clone()),
+                content_type: None,
+                width: image.width,
+                height: image.height,
+            })
+        }
+        // Articles, site previews and other rich embeds are no memes, even with a picture.
+        _ => None,
+    }
+}
+
+/// Hosts of Twitter/X and Reddit, whose posts Discord previews as rich embeds.
+const MEME_POST_HOSTS: [&str; 4] = ["twitter.com", "x.com", "reddit.com", "redd.it"];
+
+/// Whether `embed` previews a post of a site memes are shared from, see `MEME_POST_HOSTS`.
+fn is_meme_post(embed: &Embed) -> bool {
+    let provider = embed
+        .provider
+        .as_ref()
+        .and_then(|provider| provider.name.as_deref())
+        .map(str::to_ascii_lowercase);
+    if matches!(provider.as_deref(), Some("twitter" | "x" | "reddit")) {
+        return true;
+    }
+    let host = embed.url.as_deref().map(host).unwrap_or_default();
+    MEME_POST_HOSTS
+        .iter()
+        .any(|known| host == *known || host.ends_with(format!(".{known}").as_str()))
+}
+
+/// The lowercase host of `url`, empty if there is none.
+fn host(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    host.split(':')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn image_kind(url: &str) -> MediaKind {
+    match file_extension(url).as_str() {
+        "gif" => MediaKind::Gif,
+        _ => MediaKind::Image,
+    }
+}
+
+/// The lowercase extension of the file `url` points to, empty if there is none.
+fn file_extension(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let file = path.rsplit('/').next().unwrap_or_default();
+    match file.rsplit_once('.') {
+        Some((_, extension)) => extension.to_ascii_lowercase(),
+        None => String::new(),
+    }
+}
+
+fn is_image_extension(extension: &str) -> bool {
+    matches!(extension, "png" | "jpg" | "jpeg" | "gif" | "webp")
+}
+
+/// The last path segment of `url` (or a bare file name), reduced to characters that are safe in
+/// attachment and file names.
+pub fn file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let name: String = path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() {
+        "meme".to_string()
+    } else {
+        name.to_string()
+    }
+}
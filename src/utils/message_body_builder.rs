@@ -1,8 +1,17 @@
-use crate::models::discord::{Embed, EmbedImage, EmbedThumbnail, MessageBody};
+use crate::discord_api::uploads::FileUpload;
+use crate::models::discord::{AllowedMentions, Embed, MessageBody, MessageReference};
+use crate::prelude::*;
+use crate::utils::embed_builder::EmbedBuilder;
+use crate::utils::media::Media;
+use crate::utils::message_limits::{fit, Overflow};
 
 pub struct MessageBodyBuilder {
     content: String,
     embeds: Option<Vec<Embed>>,
+    /// Files along with the index of the embed they belong to, if any
+    files: Vec<(Option<usize>, FileUpload)>,
+    allowed_mentions: Option<AllowedMentions>,
+    message_reference: Option<MessageReference>,
 }
 
 impl MessageBodyBuilder {
@@ -10,6 +19,9 @@ impl MessageBodyBuilder {
         MessageBodyBuilder {
             content: content.to_string(),
             embeds: None,
+            files: Vec::new(),
+            allowed_mentions: None,
+            message_reference: None,
         }
     }
 
@@ -18,6 +30,15 @@ impl MessageBodyBuilder {
         self
     }
 
+    /// Appends `line` to the content, e.g. a link Discord should unfurl below the text.
+    pub fn add_line(mut self, line: &str) -> Self {
+        if !self.content.is_empty() {
+            self.content.push('\n');
+        }
+        self.content.push_str(line);
+        self
+    }
+
     pub fn add_embed(mut self, embed: Embed) -> Self {
         match self.embeds {
             Some(ref mut embeds) => embeds.push(embed),
@@ -26,33 +47,86 @@ impl MessageBodyBuilder {
         self
     }
 
+    /// Attaches `file` to the embed added last, if any, embeds can show it via
+    /// `FileUpload::attachment_url`.
+    pub fn add_file(mut self, file: FileUpload) -> Self {
+        let owner = self
+            .embeds
+            .as_ref()
+            .and_then(|embeds| embeds.len().checked_sub(1));
+        self.files.push((owner, file));
+        self
+    }
+
+    /// Restricts who the mentions in the content notify, Discord notifies everyone otherwise.
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+
+    /// Makes the message a reply to `message_reference`.
+    pub fn reply_to(mut self, message_reference: MessageReference) -> Self {
+        self.message_reference = Some(message_reference);
+        self
+    }
+
     pub fn build(self) -> MessageBody {
-        MessageBody {
+        self.build_with_files().0
+    }
+
+    /// Builds the message body along with the files to upload with it.
+    pub fn build_with_files(self) -> (MessageBody, Vec<FileUpload>) {
+        let (body, files) = self.build_with_owners();
+        (body, files.into_iter().map(|(_, file)| file).collect())
+    }
+
+    fn build_with_owners(self) -> (MessageBody, Vec<(Option<usize>, FileUpload)>) {
+        let body = MessageBody {
             content: self.content,
             tts: Option::from(false),
             embeds: self.embeds,
             sticker_ids: None,
             payload_json: None,
             flags: None,
-        }
+            attachments: None,
+            allowed_mentions: self.allowed_mentions,
+            message_reference: self.message_reference,
+        };
+        (body, self.files)
+    }
+
+    /// Like `build_with_files`, but makes the message fit Discord's limits, see `fit`.
+    pub fn build_messages(self, overflow: Overflow) -> Result<Vec<(MessageBody, Vec<FileUpload>)>> {
+        let (body, files) = self.build_with_owners();
+        fit(body, files, overflow)
     }
 }
 
 impl Embed {
     pub fn new(title: &str, description: &str, url: &str) -> Embed {
-        Embed {
-            title: title.to_string(),
-            description: description.to_string(),
-            fields: None,
-            author: None,
-            footer: None,
-            color: None,
-            image: Some(EmbedImage {
-                url: url.to_string(),
-            }),
-            thumbnail: Some(EmbedThumbnail {
-                String: url.to_string(),
-            }),
+        EmbedBuilder::new()
+            .title(title)
+            .description(description)
+            .image(url)
+            .build()
+    }
+
+    /// An embed showing `media`.
+    ///
+    /// Images and GIFs become the embed image. Videos and files Discord cannot render are not
+    /// shown inside an embed, the title links to them and their still frame, if any, becomes
+    /// the thumbnail.
+    pub fn with_media(title: &str, description: &str, media: &Media) -> Embed {
+        let builder = EmbedBuilder::new().title(title).description(description);
+        if media.is_embeddable() {
+            let url = media.preview_url.as_ref().unwrap_or(&media.url);
+            return builder.sized_image(url, media.width, media.height).build();
+        }
+
+        let builder = builder.url(media.url.as_str());
+        match &media.preview_url {
+            Some(preview_url) => builder.thumbnail(preview_url).build(),
+            None => builder.build(),
         }
     }
 }
@@ -0,0 +1,330 @@
+use std::str::FromStr;
+
+use log::warn;
+use serde::Deserialize;
+
+use crate::discord_api::uploads::{FileUpload, MAX_FILES};
+use crate::models::discord::{Embed, MessageBody};
+use crate::prelude::*;
+
+/// Characters of a message's content
+pub const MAX_CONTENT: usize = 2000;
+/// Embeds of a message
+pub const MAX_EMBEDS: usize = 10;
+pub const MAX_TITLE: usize = 256;
+pub const MAX_DESCRIPTION: usize = 4096;
+pub const MAX_FIELDS: usize = 25;
+pub const MAX_FIELD_NAME: usize = 256;
+pub const MAX_FIELD_VALUE: usize = 1024;
+pub const MAX_FOOTER_TEXT: usize = 2048;
+pub const MAX_AUTHOR_NAME: usize = 256;
+/// Characters of the texts of all embeds of a message together, see `embed_length`
+pub const MAX_EMBEDS_TOTAL: usize = 6000;
+
+/// What to do with a message exceeding Discord's limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Overflow {
+    /// Fail with an error naming the exceeded limit
+    Reject,
+    /// Shorten texts that are too long and drop the embeds that do not fit
+    Truncate,
+    /// Shorten texts that are too long and send the rest in further messages
+    Split,
+}
+
+impl FromStr for Overflow {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "reject" => Ok(Overflow::Reject),
+            "truncate" => Ok(Overflow::Truncate),
+            "split" => Ok(Overflow::Split),
+            _ => Err(Error::MessageLimit(format!(
+                "Unknown overflow mode {value}"
+            ))),
+        }
+    }
+}
+
+/// Checks `body` against Discord's limits, the error names the first one exceeded.
+pub fn validate(body: &MessageBody) -> Result<()> {
+    check("Content", body.content.as_str(), MAX_CONTENT)?;
+    let embeds = body.embeds.as_deref().unwrap_or_default();
+    if embeds.len() > MAX_EMBEDS {
+        return Err(Error::MessageLimit(format!(
+            "A message can carry at most {MAX_EMBEDS} embeds, not {}",
+            embeds.len()
+        )));
+    }
+    for (idx, embed) in embeds.iter().enumerate() {
+        validate_embed(embed).map_err(|err| Error::MessageLimit(format!("Embed {idx}: {err}")))?;
+    }
+    let total: usize = embeds.iter().map(embed_length).sum();
+    if total > MAX_EMBEDS_TOTAL {
+        return Err(Error::MessageLimit(format!(
+            "The embeds have {total} characters, more than the {MAX_EMBEDS_TOTAL} allowed"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_embed(embed: &Embed) -> Result<()> {
+    check("Title", embed.title.as_str(), MAX_TITLE)?;
+    check("Description", embed.description.as_str(), MAX_DESCRIPTION)?;
+    let fields = embed.fields.as_deref().unwrap_or_default();
+    if fields.len() > MAX_FIELDS {
+        return Err(Error::MessageLimit(format!(
+            "An embed can have at most {MAX_FIELDS} fields, not {}",
+            fields.len()
+        )));
+    }
+    for field in fields {
+        check("Field name", field.title.as_str(), MAX_FIELD_NAME)?;
+        check("Field value", field.value.as_str(), MAX_FIELD_VALUE)?;
+    }
+    if let Some(footer) = &embed.footer {
+        check("Footer text", footer.text.as_str(), MAX_FOOTER_TEXT)?;
+    }
+    if let Some(author) = &embed.author {
+        check("Author name", author.name.as_str(), MAX_AUTHOR_NAME)?;
+    }
+    Ok(())
+}
+
+fn check(what: &str, text: &str, max: usize) -> Result<()> {
+    let len = text.chars().count();
+    if len > max {
+        return Err(Error::MessageLimit(format!(
+            "{what} has {len} characters, more than the {max} allowed"
+        )));
+    }
+    Ok(())
+}
+
+/// The characters of `embed` counting towards `MAX_EMBEDS_TOTAL`.
+pub fn embed_length(embed: &Embed) -> usize {
+    let fields: usize = embed
+        .fields
+        .iter()
+        .flatten()
+        .map(|field| field.title.chars().count() + field.value.chars().count())
+        .sum();
+    embed.title.chars().count()
+        + embed.description.chars().count()
+        + fields
+        + embed
+            .footer
+            .as_ref()
+            .map_or(0, |footer| footer.text.chars().count())
+        + embed
+            .author
+            .as_ref()
+            .map_or(0, |author| author.name.chars().count())
+}
+
+/// `text` shortened to `max` characters, ending with an ellipsis if anything was cut.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut result: String = text.chars().take(max.saturating_sub(1)).collect();
+    result.push('…');
+    result
+}
+
+/// Shortens every text of `embed` to its limit and drops the fields beyond `MAX_FIELDS`.
+pub fn truncate_embed(embed: &mut Embed) {
+    embed.title = truncate(embed.title.as_str(), MAX_TITLE);
+    embed.description = truncate(embed.description.as_str(), MAX_DESCRIPTION);
+    if let Some(fields) = &mut embed.fields {
+        fields.truncate(MAX_FIELDS);
+        for field in fields {
+            field.title = truncate(field.title.as_str(), MAX_FIELD_NAME);
+            field.value = truncate(field.value.as_str(), MAX_FIELD_VALUE);
+        }
+    }
+    if let Some(footer) = &mut embed.footer {
+        footer.text = truncate(footer.text.as_str(), MAX_FOOTER_TEXT);
+    }
+    if let Some(author) = &mut embed.author {
+        author.name = truncate(author.name.as_str(), MAX_AUTHOR_NAME);
+    }
+}
+
+/// Makes `body` fit Discord's limits according to `overflow`, returning the messages to send
+/// in order along with their files.
+///
+/// `files` come with the index of the embed they belong to, if any. A file travels with the
+/// message of the first embed showing it, or else of the embed it belongs to, the others with
+/// the first message. No message carries more than `MAX_FILES` files.
+pub fn fit(
+    body: MessageBody,
+    files: Vec<(Option<usize>, FileUpload)>,
+    overflow: Overflow,
+) -> Result<Vec<(MessageBody, Vec<FileUpload>)>> {
+    if overflow == Overflow::Reject {
+        validate(&body)?;
+        check_files(files.len())?;
+        return Ok(vec![(
+            body,
+            files.into_iter().map(|(_, file)| file).collect(),
+        )]);
+    }
+
+    let mut embeds = body.embeds.clone().unwrap_or_default();
+    let owners: Vec<Option<usize>> = files
+        .iter()
+        .map(|(owner, file)| {
+            embeds
+                .iter()
+                .position(|embed| shows(embed, file))
+                .or(*owner)
+        })
+        .collect();
+    let files_per_embed: Vec<usize> = (0..embeds.len())
+        .map(|idx| owners.iter().filter(|owner| **owner == Some(idx)).count())
+        .collect();
+    let ownerless = owners.iter().filter(|owner| owner.is_none()).count();
+    check_files(ownerless)?;
+
+    embeds.iter_mut().for_each(truncate_embed);
+    let mut contents = match overflow {
+        Overflow::Split => split_lines(body.content.as_str(), MAX_CONTENT),
+        _ => vec![truncate(body.content.as_str(), MAX_CONTENT)],
+    };
+    let mut groups = group_embeds(embeds, &files_per_embed, ownerless);
+    if overflow == Overflow::Truncate && groups.len() > 1 {
+        let dropped: usize = groups.drain(1..).map(|group| group.len()).sum();
+        warn!("Dropping {dropped} embeds exceeding Discord's limits");
+    }
+    let group_of: Vec<usize> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, group)| std::iter::repeat_n(idx, group.len()))
+        .collect();
+
+    let count = contents.len().max(groups.len()).max(1);
+    contents.resize(count, String::new());
+    groups.resize(count, Vec::new());
+    let mut messages: Vec<(MessageBody, Vec<FileUpload>)> = contents
+        .into_iter()
+        .zip(groups)
+        .enumerate()
+        .map(|(idx, (content, group))| {
+            let message = MessageBody {
+                content,
+                tts: body.tts,
+                embeds: (!group.is_empty()).then_some(group),
+                sticker_ids: if idx == 0 {
+                    body.sticker_ids.clone()
+                } else {
+                    None
+                },
+                payload_json: None,
+                flags: body.flags,
+                attachments: None,
+                allowed_mentions: body.allowed_mentions.clone(),
+                message_reference: if idx == 0 {
+                    body.message_reference.clone()
+                } else {
+                    None
+                },
+            };
+            (message, Vec::new())
+        })
+        .collect();
+
+    for (owner, (_, file)) in owners.into_iter().zip(files) {
+        // Files of embeds dropped by truncation are not worth uploading.
+        if let Some(idx) = owner.map_or(Some(0), |owner| group_of.get(owner).copied()) {
+            messages[idx].1.push(file);
+        }
+    }
+    for (_, files) in &messages {
+        check_files(files.len())?;
+    }
+    Ok(messages)
+}
+
+fn check_files(count: usize) -> Result<()> {
+    if count > MAX_FILES {
+        return Err(Error::MessageLimit(format!(
+            "A message can carry at most {MAX_FILES} files, not {count}"
+        )));
+    }
+    Ok(())
+}
+
+/// Splits `text` at line breaks into chunks of at most `max` characters, lines longer than
+/// that are truncated.
+fn split_lines(text: &str, max: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut chunk = String::new();
+    let mut chunk_len = 0;
+    for line in text.split('\n') {
+        let line = truncate(line, max);
+        let line_len = line.chars().count();
+        if !chunk.is_empty() && chunk_len + 1 + line_len > max {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_len = 0;
+        }
+        if !chunk.is_empty() {
+            chunk.push('\n');
+            chunk_len += 1;
+        }
+        chunk.push_str(line.as_str());
+        chunk_len += line_len;
+    }
+    if !chunk.is_empty() || chunks.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Groups `embeds` in order so that no group exceeds `MAX_EMBEDS` or `MAX_EMBEDS_TOTAL`, nor
+/// `MAX_FILES` counting the `files` of each embed and the `ownerless` files of the first group.
+fn group_embeds(embeds: Vec<Embed>, files: &[usize], ownerless: usize) -> Vec<Vec<Embed>> {
+    let mut groups: Vec<Vec<Embed>> = Vec::new();
+    let mut group: Vec<Embed> = Vec::new();
+    let mut total = 0;
+    let mut group_files = ownerless;
+    for (embed, embed_files) in embeds.into_iter().zip(files) {
+        let len = embed_length(&embed);
+        if !group.is_empty()
+            && (group.len() == MAX_EMBEDS
+                || total + len > MAX_EMBEDS_TOTAL
+                || group_files + embed_files > MAX_FILES)
+        {
+            groups.push(std::mem::take(&mut group));
+            total = 0;
+            group_files = 0;
+        }
+        total += len;
+        group_files += embed_files;
+        group.push(embed);
+    }
+    if !group.is_empty() {
+        groups.push(group);
+    }
+    groups
+}
+
+/// Whether `embed` shows `file` via its `attachment://` url.
+fn shows(embed: &Embed, file: &FileUpload) -> bool {
+    let url = file.attachment_url();
+    let urls = [
+        embed.image.as_ref().map(|image| &image.url),
+        embed.thumbnail.as_ref().map(|thumbnail| &thumbnail.url),
+        embed
+            .author
+            .as_ref()
+            .and_then(|author| author.icon_url.as_ref()),
+        embed
+            .footer
+            .as_ref()
+            .and_then(|footer| footer.icon_url.as_ref()),
+    ];
+    urls.into_iter().flatten().any(|shown| *shown == url)
+}
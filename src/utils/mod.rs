@@ -1,3 +1,15 @@
+pub mod archive;
+pub mod cdn_expiry;
+pub mod clock;
+pub mod competition;
 pub mod config;
+pub mod embed_builder;
+pub mod i18n;
 pub mod last_month_date;
+pub mod media;
 pub mod message_body_builder;
+pub mod message_limits;
+pub mod period;
+pub mod snowflake;
+pub mod state;
+pub mod template;
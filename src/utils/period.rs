@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Months, TimeZone, Utc};
+
+use crate::prelude::*;
+use crate::utils::clock::Clock;
+use crate::utils::i18n::Locale;
+
+/// A half-open time range `[start, end)` memes are collected for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Period {
+    /// The calendar month `date` falls into.
+    pub fn month_of(date: &DateTime<Utc>) -> Result<Self> {
+        let start = Utc
+            .with_ymd_and_hms(date.year(), date.month(), 1, 0, 0, 0)
+            .single()
+            .ok_or(Error::Static("Invalid month"))?;
+        let end = start
+            .checked_add_months(Months::new(1))
+            .ok_or(Error::Static("Invalid month"))?;
+        Ok(Self { start, end })
+    }
+
+    /// The calendar month before the one `clock` is in.
+    pub fn last_month(clock: &impl Clock) -> Result<Self> {
+        let date = clock
+            .now()
+            .checked_sub_months(Months::new(1))
+            .ok_or(Error::Static("Invalid month"))?;
+        Self::month_of(&date)
+    }
+
+    /// The month and year of the period in `locale`, e.g. `March 2024` or `März 2024`.
+    pub fn name(&self, locale: Locale) -> Result<String> {
+        let month = locale.text(format!("month.{}", self.start.month()).as_str());
+        let vars = HashMap::from([("month", month), ("year", self.start.year().to_string())]);
+        locale.format("period.month", &vars)
+    }
+
+    pub fn contains(&self, timestamp: &DateTime<Utc>) -> bool {
+        self.start <= *timestamp && *timestamp < self.end
+    }
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Milliseconds between the unix epoch and the first second of 2015, the Discord epoch.
+const DISCORD_EPOCH: i64 = 1_420_070_400_000;
+
+/// Returns the smallest snowflake that could have been created at `timestamp`.
+///
+/// Discord accepts these as `before`/`after` cursors, which lets us page through a channel's
+/// history starting at an arbitrary point in time.
+pub fn snowflake_from_timestamp(timestamp: &DateTime<Utc>) -> u64 {
+    let millis = (timestamp.timestamp_millis() - DISCORD_EPOCH).max(0);
+    (millis as u64) << 22
+}
+
+/// Returns the time at which the snowflake `id` was created.
+pub fn timestamp_from_snowflake(id: u64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt((id >> 22) as i64 + DISCORD_EPOCH)
+        .single()
+}
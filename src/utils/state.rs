@@ -0,0 +1,37 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::models::state::BotState;
+use crate::prelude::*;
+
+/// A JSON file keeping the `BotState` between runs.
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Reads the state, a fresh one if the file does not exist yet.
+    pub fn load(&self) -> Result<BotState> {
+        match fs::read_to_string(&self.path) {
+            Ok(json) => Ok(serde_json::from_str(json.as_str())?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(BotState::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes `state`, replacing the file only once it is written completely.
+    pub fn save(&self, state: &BotState) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+        fs::rename(&tmp, &self.path)?;
+        debug!("Saved state to {:?}", self.path);
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::prelude::*;
+use crate::utils::i18n::Locale;
+
+/// Text with `{variable}` placeholders, `{{` and `}}` stand for literal braces.
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Variable(String),
+}
+
+impl Template {
+    /// Renders the template, failing on variables missing from `vars`.
+    pub fn render(&self, vars: &HashMap<&str, String>) -> Result<String> {
+        let mut result = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => result.push_str(text),
+                Segment::Variable(name) => match vars.get(name.as_str()) {
+                    Some(value) => result.push_str(value),
+                    None => {
+                        return Err(Error::Generic(format!(
+                            "Unknown variable {{{name}}} in template \"{}\"",
+                            self.source
+                        )))
+                    }
+                },
+            }
+        }
+        Ok(result)
+    }
+
+    /// Checks that every variable of the template is one of `known`.
+    pub fn check(&self, known: &[&str]) -> Result<()> {
+        for segment in &self.segments {
+            if let Segment::Variable(name) = segment {
+                if !known.contains(&name.as_str()) {
+                    return Err(Error::Generic(format!(
+                        "Unknown variable {{{name}}} in template \"{}\"",
+                        self.source
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let unbalanced = || Error::Generic(format!("Unbalanced braces in template \"{source}\""));
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut text = String::new();
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => return Err(unbalanced()),
+                            Some(c) => name.push(c),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Variable(name.trim().to_string()));
+                }
+                '}' => return Err(unbalanced()),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+}
+
+impl TryFrom<String> for Template {
+    type Error = Error;
+
+    fn try_from(source: String) -> Result<Self> {
+        source.parse()
+    }
+}
+
+impl fmt::Debug for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Template({:?})", self.source)
+    }
+}
+
+/// The variables of `AnnouncementTemplates::intro`.
+pub const INTRO_VARS: [&str; 3] = ["competition", "period", "count"];
+/// The variables of the embed templates.
+pub const EMBED_VARS: [&str; 9] = [
+    "competition",
+    "period",
+    "count",
+    "place",
+    "author",
+    "author_name",
+    "score",
+    "channel",
+    "link",
+];
+/// The variables of `AnnouncementTemplates::topic`.
+pub const TOPIC_VARS: [&str; 5] = ["competition", "period", "count", "place", "author_name"];
+
+/// The texts of an announcement.
+///
+/// `intro` knows `{competition}`, `{period}` and `{count}`, the number of winners. The embed
+/// templates know these too, plus `{place}`, `{author}` (a mention), `{author_name}`,
+/// `{score}`, `{channel}` (a mention of the input channel) and `{link}` to the meme's message.
+/// `topic` knows the variables of `intro`, plus `{place}` and `{author_name}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnnouncementTemplates {
+    /// The content of the announcement
+    pub intro: Template,
+    pub title: Template,
+    pub description: Template,
+    /// Left out if empty
+    pub footer: Template,
+    /// The topic of `topic_channel_id` naming the winner
+    pub topic: Template,
+}
+
+impl AnnouncementTemplates {
+    /// The texts of the catalog of `locale`.
+    pub fn for_locale(locale: Locale) -> Self {
+        let template = |key: &str| locale.template(key).expect("Catalog templates are valid");
+        Self {
+            intro: template("announcement.intro"),
+            title: template("announcement.title"),
+            description: template("announcement.description"),
+            footer: template("announcement.footer"),
+            topic: template("announcement.topic"),
+        }
+    }
+
+    /// Checks that every template only uses the variables it is rendered with.
+    pub fn validate(&self) -> Result<()> {
+        self.intro.check(&INTRO_VARS)?;
+        self.title.check(&EMBED_VARS)?;
+        self.description.check(&EMBED_VARS)?;
+        self.footer.check(&EMBED_VARS)?;
+        self.topic.check(&TOPIC_VARS)
+    }
+}